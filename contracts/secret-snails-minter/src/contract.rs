@@ -1,18 +1,26 @@
 use cosmwasm_std::{
-    from_binary, to_binary, Api, Binary, Env, Extern, HandleResponse, HumanAddr, InitResponse,
-    Querier, StdError, StdResult, Storage, Uint128,
+    from_binary, log, to_binary, to_vec, Api, BankMsg, Binary, Coin, CosmosMsg, Env, Extern,
+    HandleResponse, HumanAddr, InitResponse, Querier, ReadonlyStorage, StdError, StdResult,
+    Storage, Uint128,
 };
-use cosmwasm_storage::PrefixedStorage;
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use rand::prelude::SliceRandom;
 use rand::{Rng, RngCore};
 use secret_toolkit::crypto::Prng;
-use secret_toolkit::snip20::{send_msg, transfer_msg};
+use secret_toolkit::permit::{validate, Permission, Permit, RevokedPermits};
+use secret_toolkit::snip20::{balance_query, send_msg, set_viewing_key_msg, transfer_msg};
 
 use crate::msg::{
     Authentication, Extension, HandleAnswer, HandleReceiveMsg, HiddenAttribute, MediaFile,
-    Metadata, Mint, NftsHandleMsg, PreLoad, QueryAnswer, ResponseStatus, Trait,
+    Metadata, Mint, MintPhase, MintPhaseInfo, NftsHandleMsg, NftsInitMsg, NftsQueryAnswer,
+    NftsQueryMsg, PendingMintInfo, PhaseMintStatus, PostInitCallback, PreLoad, QueryAnswer,
+    QueryWithPermit, ResponseStatus, RevenueSplit, RoleHolders, TokenBalance, Trait,
+    WhitelistEntry, WhitelistProof,
+};
+use crate::state::{
+    load, may_load, save, AuctionPurchase, ContractStatus, DutchAuction, PaymentToken, PendingMint,
+    Role, SecretContract, StoredMintPhase, BLOCK_SIZE,
 };
-use crate::state::{load, may_load, save, SecretContract, BLOCK_SIZE};
 use crate::{
     msg::{HandleMsg, InitMsg, QueryMsg},
     state::Config,
@@ -20,27 +28,83 @@ use crate::{
 use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::{ChaCha20Rng, ChaChaRng};
 use secret_toolkit::storage::{TypedStore, TypedStoreMut};
-use secret_toolkit::utils::{HandleCallback, InitCallback};
+use secret_toolkit::utils::{HandleCallback, InitCallback, Query};
 use secret_toolkit::{crypto::sha_256, snip20::register_receive_msg};
 use sha2::{Digest, Sha256};
 
 pub const COUNT_KEY: &[u8] = b"count";
 pub const CONFIG_KEY: &[u8] = b"config";
 pub const PREFIX_WHITELIST: &[u8] = b"whitelistprefix";
+/// number of slots of the whitelist index.  Addresses get a slot the first time they are
+/// whitelisted and keep it when removed, so the index is only ever appended to
+pub const WHITELIST_SLOTS_KEY: &[u8] = b"whitelistslots";
+/// number of addresses currently whitelisted
+pub const WHITELIST_TOTAL_KEY: &[u8] = b"whitelisttotal";
+pub const PREFIX_WHITELIST_INDEX: &[u8] = b"whitelistindexprefix";
+pub const PREFIX_WHITELIST_SLOT: &[u8] = b"whitelistslotprefix";
+pub const MY_ADDRESS_KEY: &[u8] = b"myaddress";
+pub const PREFIX_REVOKED_PERMITS: &str = "revoke";
+pub const WHITELIST_MERKLE_ROOT_KEY: &[u8] = b"whitelistmerkleroot";
+pub const PREFIX_WHITELIST_LEAVES: &[u8] = b"whitelistleavesprefix";
 pub const PRNG_SEED_KEY: &[u8] = b"prngseed";
+pub const PAYMENT_TOKENS_KEY: &[u8] = b"paymenttokens";
+/// number of tokens reserved by commit-reveal mints that were not revealed yet
+pub const RESERVED_KEY: &[u8] = b"reserved";
+pub const PENDING_MINTS_COUNT_KEY: &[u8] = b"pendingmintscount";
+pub const PENDING_MINT_IDS_KEY: &[u8] = b"pendingmintids";
+pub const PREFIX_PENDING_MINTS: &[u8] = b"pendingmintsprefix";
+pub const ENTROPY_POOL_KEY: &[u8] = b"entropypool";
+pub const MINT_PHASES_KEY: &[u8] = b"mintphases";
+pub const MINT_PHASES_COUNT_KEY: &[u8] = b"mintphasescount";
+pub const PREFIX_PHASE_ALLOWED: &[u8] = b"phaseallowedprefix";
+pub const PREFIX_PHASE_MINTED: &[u8] = b"phasemintedprefix";
+pub const PREFIX_WALLET_MINTED: &[u8] = b"walletmintedprefix";
+pub const MINTED_COUNT_KEY: &[u8] = b"mintedcount";
+pub const PENDING_NFT_CODE_HASH_KEY: &[u8] = b"pendingnftcodehash";
+/// loaded tokens.  The first version of the contract kept them under u16 keys in the root
+/// namespace, which are not read: contracts can't be migrated, so a new minter is deployed
+pub const PREFIX_PRELOAD: &[u8] = b"preloadprefix";
+pub const TOKEN_VIEWING_KEYS_KEY: &[u8] = b"tokenviewingkeys";
+pub const PENDING_ADMIN_KEY: &[u8] = b"pendingadmin";
+pub const PREFIX_ROLES: &[u8] = b"roles";
+pub const CONTRACT_STATUS_KEY: &[u8] = b"contractstatus";
+pub const PREFIX_POOL: &[u8] = b"poolprefix";
+pub const PREFIX_PRELOADED_IDS: &[u8] = b"preloadedidsprefix";
+pub const PRELOAD_HASH_KEY: &[u8] = b"preloadhash";
+pub const METADATA_LOCKED_KEY: &[u8] = b"metadatalocked";
+pub const PREFIX_PHASE_TOTAL_MINTED: &[u8] = b"phasetotalmintedprefix";
+pub const DUTCH_AUCTION_KEY: &[u8] = b"dutchauction";
+pub const LAST_AUCTION_PRICE_KEY: &[u8] = b"lastauctionprice";
+pub const PREFIX_AUCTION_PURCHASES: &[u8] = b"auctionpurchasesprefix";
+/// number of dutch auction purchases waiting for their buyers to claim them
+pub const AUCTION_UNCLAIMED_KEY: &[u8] = b"auctionunclaimed";
+/// funds kept for dutch auction purchases that were not claimed yet, by funds key
+pub const PREFIX_AUCTION_OWED: &[u8] = b"auctionowedprefix";
+/// 100% expressed with the 4 decimal places used by RevenueSplit percentages
+pub const REVENUE_SPLIT_TOTAL: u32 = 1_000_000;
+pub const WINS_TRAIT: &str = "Wins";
+pub const LOSES_TRAIT: &str = "Loses";
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    validate_revenue_split(&msg.revenue_split)?;
+
     let mut config_store = TypedStoreMut::attach(&mut deps.storage);
     let admin = msg.admin.unwrap_or(env.message.sender);
+    let sealed_metadata = msg
+        .nft_contract_init
+        .as_ref()
+        .and_then(|nft_init| nft_init.config.as_ref())
+        .and_then(|config| config.enable_sealed_metadata)
+        .unwrap_or(false);
 
     config_store.store(
         CONFIG_KEY,
         &Config {
-            admin,
+            admin: admin.clone(),
             token_contract: msg.token_contract.clone(),
             nft_contract: None,
             mint_price: msg.mint_price.clone(),
@@ -49,25 +113,64 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
             standard_mint_enabled: false,
             revenue_split: msg.revenue_split.clone(),
             change_metadata_permited_addresses: vec![],
+            commit_reveal_enabled: false,
+            native_denom: msg.native_denom,
+            max_mint_per_wallet: msg.max_mint_per_wallet,
+            sealed_metadata,
         },
     )?;
 
     let prng_seed: Vec<u8> = sha_256(base64::encode(msg.entropy).as_bytes()).to_vec();
     save(&mut deps.storage, PRNG_SEED_KEY, &prng_seed)?;
-    save(&mut deps.storage, COUNT_KEY, &0)?;
-    let mut white_store = PrefixedStorage::new(PREFIX_WHITELIST, &mut deps.storage);
-    for hum_addr in msg.whitelist.iter() {
-        save(&mut white_store, &hum_addr.0.as_bytes(), &false)?;
+    save(&mut deps.storage, COUNT_KEY, &0u32)?;
+    save(&mut deps.storage, MY_ADDRESS_KEY, &env.contract.address)?;
+    save_whitelist_allowances(&mut deps.storage, &msg.whitelist)?;
+    if let Some(root) = msg.whitelist_merkle_root {
+        set_merkle_root(&mut deps.storage, Some(root))?;
     }
 
-    Ok(InitResponse {
-        messages: vec![register_receive_msg(
-            env.contract_code_hash.clone(),
+    let mut messages = vec![register_receive_msg(
+        env.contract_code_hash.clone(),
+        None,
+        1,
+        msg.token_contract.token_code_hash.clone(),
+        msg.token_contract.contract_addr.clone(),
+    )?];
+
+    if let Some(nft_init) = msg.nft_contract_init {
+        // the NFT contract gets the code hash from here, its address comes with the callback
+        save(
+            &mut deps.storage,
+            PENDING_NFT_CODE_HASH_KEY,
+            &nft_init.code_hash,
+        )?;
+
+        let nft_init_msg = NftsInitMsg {
+            name: nft_init.name,
+            symbol: nft_init.symbol,
+            admin: Some(admin),
+            entropy: base64::encode(sha_256(&[prng_seed.as_slice(), b"nft"].concat())),
+            royalty_info: nft_init.royalty_info,
+            config: nft_init.config,
+            post_init_callback: Some(PostInitCallback {
+                msg: to_binary(&HandleMsg::RegisterNftContract {})?,
+                contract_address: env.contract.address.clone(),
+                code_hash: env.contract_code_hash.clone(),
+                send: vec![],
+            }),
+            hidden_attribute_viewers: nft_init.hidden_attribute_viewers,
+            minters: Some(vec![env.contract.address.clone()]),
+        };
+        messages.push(nft_init_msg.to_cosmos_msg(
+            nft_init.label,
+            nft_init.code_id,
+            nft_init.code_hash,
             None,
-            1,
-            msg.token_contract.token_code_hash.clone(),
-            msg.token_contract.contract_addr.clone(),
-        )?],
+        )?);
+    }
+
+    Ok(InitResponse {
+        messages,
         log: vec![],
     })
 }
@@ -78,11 +181,18 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
     match msg {
+        HandleMsg::SetContractStatus { .. } | HandleMsg::Receive { .. } => {}
+        _ => check_status(&deps.storage, |_| false)?,
+    }
+
+    match msg {
+        HandleMsg::SetContractStatus { status } => set_contract_status(deps, env, status),
         HandleMsg::UpdateMint {
             whitelist_mint_enabled,
             standard_mint_enabled,
             mint_price,
             max_mint_per_tx,
+            commit_reveal_enabled,
         } => update_mint(
             deps,
             env,
@@ -90,6 +200,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             standard_mint_enabled,
             mint_price,
             max_mint_per_tx,
+            commit_reveal_enabled,
         ),
         HandleMsg::Receive {
             sender,
@@ -97,14 +208,82 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             amount,
             msg,
         } => try_receive(deps, env, sender, from, amount, msg),
-        HandleMsg::AddNftContract { contract } => add_nft_contract(deps, env, contract),
+        HandleMsg::MintNfts {
+            count,
+            entropy,
+            whitelist_proof,
+            partial_fill,
+        } => try_mint_native(
+            deps,
+            env,
+            MintRequest {
+                count,
+                entropy,
+                whitelist_proof,
+                partial_fill: partial_fill.unwrap_or(false),
+            },
+        ),
+        HandleMsg::AddNftContract {
+            contract,
+            sealed_metadata,
+        } => add_nft_contract(deps, env, contract, sealed_metadata.unwrap_or(false)),
+        HandleMsg::RegisterNftContract {} => register_nft_contract(deps, env),
+        HandleMsg::UpdateRevenueSplit { revenue_split } => {
+            update_revenue_split(deps, env, revenue_split)
+        }
+        HandleMsg::WithdrawToken {
+            token,
+            amount,
+            recipient,
+        } => withdraw(
+            deps,
+            env,
+            Payment::Snip20 {
+                token_contract: token,
+                amount,
+                price: None,
+            },
+            recipient,
+        ),
+        HandleMsg::WithdrawNative {
+            denom,
+            amount,
+            recipient,
+        } => withdraw(deps, env, Payment::Native { denom, amount }, recipient),
+        HandleMsg::SetTokenViewingKey { token, key } => {
+            set_token_viewing_key(deps, env, token, key)
+        }
         HandleMsg::LoadMetadata { new_data } => load_metadata(deps, env, new_data),
-        HandleMsg::ChangeAdmin { admin } => change_admin(deps, env, admin),
+        HandleMsg::AddToWhitelist { entries } => add_to_whitelist(deps, env, entries),
+        HandleMsg::RemoveFromWhitelist { addresses } => remove_from_whitelist(deps, env, addresses),
+        HandleMsg::SetWhitelistMerkleRoot { root } => set_whitelist_merkle_root(deps, env, root),
+        HandleMsg::RevokePermit { permit_name } => revoke_permit(deps, env, permit_name),
+        HandleMsg::AddPaymentToken { token, price } => add_payment_token(deps, env, token, price),
+        HandleMsg::RemovePaymentToken { contract_addr } => {
+            remove_payment_token(deps, env, contract_addr)
+        }
+        HandleMsg::ProposeAdmin { address } => propose_admin(deps, env, address),
+        HandleMsg::AcceptAdmin {} => accept_admin(deps, env),
+        HandleMsg::CancelAdminProposal {} => cancel_admin_proposal(deps, env),
+        HandleMsg::GrantRole { role, address } => update_role(deps, env, role, address, true),
+        HandleMsg::RevokeRole { role, address } => update_role(deps, env, role, address, false),
         HandleMsg::UpdateChangeMetadataPermitedAdresses {
             change_metadata_permited_addresses,
         } => {
             update_change_metadata_permited_addresses(deps, env, change_metadata_permited_addresses)
         }
+        HandleMsg::UpdateSnailStats {
+            token_id,
+            wins_delta,
+            loses_delta,
+        } => update_snail_stats(deps, env, token_id, wins_delta, loses_delta),
+        HandleMsg::SetMintPhases { phases } => set_mint_phases(deps, env, phases),
+        HandleMsg::SetDutchAuction { auction } => set_dutch_auction(deps, env, auction),
+        HandleMsg::ClaimAuctionRebate {} => claim_auction_rebate(deps, env),
+        HandleMsg::SetMaxMintPerWallet {
+            max_mint_per_wallet,
+        } => set_max_mint_per_wallet(deps, env, max_mint_per_wallet),
+        HandleMsg::RevealMints { mint_ids } => reveal_mints(deps, env, mint_ids),
     }
 }
 
@@ -115,18 +294,20 @@ pub fn update_mint<S: Storage, A: Api, Q: Querier>(
     standard_mint_enabled: bool,
     mint_price: Option<Uint128>,
     max_mint_per_tx: Option<u16>,
+    commit_reveal_enabled: Option<bool>,
 ) -> StdResult<HandleResponse> {
-    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
-    let mut config: Config = config_store.load(CONFIG_KEY)?;
+    let mut config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
 
-    if env.message.sender != config.admin {
-        return Err(StdError::generic_err(format!(
-            "Only admin can execute this action!"
-        )));
-    }
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
 
     config.whitelist_mint_enabled = whitelist_mint_enabled;
     config.standard_mint_enabled = standard_mint_enabled;
+    let lock_metadata = whitelist_mint_enabled || standard_mint_enabled;
 
     if let Some(mint_price) = mint_price {
         config.mint_price = mint_price
@@ -136,7 +317,15 @@ pub fn update_mint<S: Storage, A: Api, Q: Querier>(
         config.max_mint_per_tx = max_mint_per_tx
     }
 
-    config_store.store(CONFIG_KEY, &config)?;
+    if let Some(commit_reveal_enabled) = commit_reveal_enabled {
+        config.commit_reveal_enabled = commit_reveal_enabled
+    }
+
+    TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
+
+    if lock_metadata {
+        save(&mut deps.storage, METADATA_LOCKED_KEY, &true)?;
+    }
 
     return Ok(HandleResponse {
         messages: vec![],
@@ -151,25 +340,18 @@ pub fn add_nft_contract<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     contract: SecretContract,
+    sealed_metadata: bool,
 ) -> StdResult<HandleResponse> {
-    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
-    let mut config: Config = config_store.load(CONFIG_KEY)?;
+    let mut config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
 
-    if env.message.sender != config.admin {
-        return Err(StdError::generic_err(format!(
-            "Only admin can execute this action!"
-        )));
-    }
+    check_role(&deps.storage, &config, &env.message.sender, Role::Admin)?;
 
-    if config.standard_mint_enabled != false || config.whitelist_mint_enabled != false {
-        return Err(StdError::generic_err(format!(
-            "Mint should be stoped to perform this"
-        )));
-    }
+    ensure_mint_stopped(&deps.storage, &config, env.block.time)?;
 
     config.nft_contract = Some(contract);
+    config.sealed_metadata = sealed_metadata;
 
-    config_store.store(CONFIG_KEY, &config)?;
+    TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
 
     return Ok(HandleResponse {
         messages: vec![],
@@ -180,241 +362,1893 @@ pub fn add_nft_contract<S: Storage, A: Api, Q: Querier>(
     });
 }
 
+/// Fails if anybody can mint right now, through the UpdateMint flags or a mint phase
+fn ensure_mint_stopped<S: ReadonlyStorage>(
+    storage: &S,
+    config: &Config,
+    time: u64,
+) -> StdResult<()> {
+    let phases: Vec<StoredMintPhase> = may_load(storage, MINT_PHASES_KEY)?.unwrap_or_default();
+
+    if config.standard_mint_enabled
+        || config.whitelist_mint_enabled
+        || active_phase(&phases, time).is_some()
+    {
+        return Err(StdError::generic_err(
+            "Mint should be stoped to perform this",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn update_revenue_split<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    revenue_split: Vec<RevenueSplit>,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(&deps.storage, &config, &env.message.sender, Role::Admin)?;
+
+    // nobody is paid while the contract status pauses the mint.  The NFT contract can't change
+    // during a pause though, since the pending commit-reveal mints still mint there
+    let status: ContractStatus = may_load(&deps.storage, CONTRACT_STATUS_KEY)?.unwrap_or_default();
+    if !status.mint_paused {
+        ensure_mint_stopped(&deps.storage, &config, env.block.time)?;
+    }
+    validate_revenue_split(&revenue_split)?;
+
+    config.revenue_split = revenue_split;
+    TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::UpdateRevenueSplit {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Sends `amount` of a SNIP-20 or of a native coin held by the minter to `recipient`.  Funds
+/// kept for dutch auction purchases that were not claimed yet can't be withdrawn
+pub fn withdraw<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    funds: Payment,
+    recipient: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(&deps.storage, &config, &env.message.sender, Role::Treasurer)?;
+
+    let owed = auction_owed(&deps.storage, &funds)?;
+    if owed > 0 {
+        let balance = funds_balance(deps, &env.contract.address, &funds)?;
+        let available = balance.saturating_sub(owed);
+        if funds.amount().u128() > available {
+            return Err(StdError::generic_err(format!(
+                "Only {} {} can be withdrawn, {} are kept for dutch auction rebates",
+                available,
+                funds.name(),
+                owed
+            )));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![funds.send_msg(&env.contract.address, &recipient, funds.amount())?],
+        log: vec![log("withdraw", funds.amount()), log("to", &recipient)],
+        data: Some(to_binary(&HandleAnswer::Withdraw {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Sets the viewing key the minter uses to query its balance of `token`
+pub fn set_token_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: SecretContract,
+    key: String,
+) -> StdResult<HandleResponse> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(&deps.storage, &config, &env.message.sender, Role::Treasurer)?;
+
+    let mut viewing_keys: Vec<(SecretContract, String)> =
+        may_load(&deps.storage, TOKEN_VIEWING_KEYS_KEY)?.unwrap_or_default();
+    viewing_keys.retain(|(stored, _)| stored.contract_addr != token.contract_addr);
+    viewing_keys.push((token.clone(), key.clone()));
+    save(&mut deps.storage, TOKEN_VIEWING_KEYS_KEY, &viewing_keys)?;
+
+    Ok(HandleResponse {
+        messages: vec![set_viewing_key_msg(
+            key,
+            None,
+            BLOCK_SIZE,
+            token.token_code_hash,
+            token.contract_addr,
+        )?],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetTokenViewingKey {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Saves the address of the NFT contract instantiated by `init`.  Only accepted once, in the
+/// same transaction as the instantiation, while the code hash given to `init` is pending
+pub fn register_nft_contract<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let code_hash: String = may_load(&deps.storage, PENDING_NFT_CODE_HASH_KEY)?
+        .ok_or_else(|| StdError::generic_err("No NFT contract is being instantiated"))?;
+    deps.storage.remove(PENDING_NFT_CODE_HASH_KEY);
+
+    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
+    let mut config: Config = config_store.load(CONFIG_KEY)?;
+    config.nft_contract = Some(SecretContract {
+        contract_addr: env.message.sender,
+        token_code_hash: code_hash,
+    });
+    config_store.store(CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterNftContract {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
 /// Lets Admin load metadata used in random minting
 pub fn load_metadata<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     new_data: Vec<PreLoad>,
 ) -> StdResult<HandleResponse> {
+    check_status(&deps.storage, |status| status.metadata_paused)?;
+
     let config_store = TypedStore::attach(&deps.storage);
     let config: Config = config_store.load(CONFIG_KEY)?;
 
-    if env.message.sender != config.admin {
-        return Err(StdError::generic_err(format!(
-            "Only admin can execute this action!"
-        )));
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MetadataLoader,
+    )?;
+
+    if may_load(&deps.storage, METADATA_LOCKED_KEY)?.unwrap_or(false) {
+        return Err(StdError::generic_err(
+            "Metadata can't be loaded once minting has been enabled",
+        ));
     }
 
-    let mut id: u16 = load(&deps.storage, COUNT_KEY)?;
+    let mut id = pool_count(&deps.storage)?;
+    let mut hash: Vec<u8> = may_load(&deps.storage, PRELOAD_HASH_KEY)?.unwrap_or_default();
 
     for data in new_data.iter() {
-        id = id + 1;
-        save(&mut deps.storage, &id.clone().to_le_bytes(), data)?;
+        if data.id.is_empty() {
+            return Err(StdError::generic_err("Token id can't be empty"));
+        }
+        if data.img_url.is_empty() {
+            return Err(StdError::generic_err(format!(
+                "Token {} has no image url",
+                data.id
+            )));
+        }
+
+        let mut ids_store = PrefixedStorage::new(PREFIX_PRELOADED_IDS, &mut deps.storage);
+        if may_load::<bool, _>(&ids_store, data.id.as_bytes())?.is_some() {
+            return Err(StdError::generic_err(format!(
+                "Token id {} is already loaded",
+                data.id
+            )));
+        }
+        save(&mut ids_store, data.id.as_bytes(), &true)?;
+
+        id = id
+            .checked_add(1)
+            .ok_or_else(|| StdError::generic_err("Too many tokens loaded"))?;
+        let mut preload_store = PrefixedStorage::new(PREFIX_PRELOAD, &mut deps.storage);
+        save(&mut preload_store, &id.to_le_bytes(), data)?;
+
+        hash = preload_hash(&hash, data)?;
     }
 
     save(&mut deps.storage, COUNT_KEY, &id)?;
+    save(&mut deps.storage, PRELOAD_HASH_KEY, &hash)?;
 
     Ok(HandleResponse::default())
 }
 
-pub fn change_admin<S: Storage, A: Api, Q: Querier>(
+/// Chains the hash of the tokens loaded so far with `data`, so the final hash commits to every
+/// loaded token and to their order
+pub fn preload_hash(hash: &[u8], data: &PreLoad) -> StdResult<Vec<u8>> {
+    let data_hash = sha_256(&to_vec(data)?);
+    Ok(sha_256(&[hash, &data_hash].concat()).to_vec())
+}
+
+pub fn propose_admin<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    admin: HumanAddr,
+    address: HumanAddr,
 ) -> StdResult<HandleResponse> {
-    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
-    let mut config: Config = config_store.load(CONFIG_KEY)?;
-
-    if env.message.sender != config.admin {
-        return Err(StdError::generic_err(format!(
-            "Only admin can execute this action!"
-        )));
-    }
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
 
-    config.admin = admin;
+    check_role(&deps.storage, &config, &env.message.sender, Role::Admin)?;
 
-    config_store.store(CONFIG_KEY, &config)?;
+    save(&mut deps.storage, PENDING_ADMIN_KEY, &address)?;
 
-    return Ok(HandleResponse {
+    Ok(HandleResponse {
         messages: vec![],
-        log: vec![],
-        data: Some(to_binary(&HandleAnswer::ChangeAdmin {
+        log: vec![log("pending_admin", &address)],
+        data: Some(to_binary(&HandleAnswer::ProposeAdmin {
             status: ResponseStatus::Success,
         })?),
-    });
+    })
 }
 
-pub fn update_change_metadata_permited_addresses<S: Storage, A: Api, Q: Querier>(
+/// Makes the proposed admin the admin, it has to send this itself so a mistyped address can't
+/// take over the contract
+pub fn accept_admin<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    change_metadata_permited_addresses: Vec<HumanAddr>,
 ) -> StdResult<HandleResponse> {
-    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
-    let mut config: Config = config_store.load(CONFIG_KEY)?;
-
-    if env.message.sender != config.admin {
-        return Err(StdError::generic_err(format!(
-            "Only admin can execute this action!"
-        )));
+    let pending_admin: Option<HumanAddr> = may_load(&deps.storage, PENDING_ADMIN_KEY)?;
+    if pending_admin.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::generic_err(
+            "Only the proposed admin can accept the admin rights",
+        ));
     }
 
-    config.change_metadata_permited_addresses = change_metadata_permited_addresses;
-
+    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
+    let mut config: Config = config_store.load(CONFIG_KEY)?;
+    config.admin = env.message.sender;
     config_store.store(CONFIG_KEY, &config)?;
+    deps.storage.remove(PENDING_ADMIN_KEY);
 
-    return Ok(HandleResponse {
+    Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(
-            &HandleAnswer::UpdateChangeMetadataPermitedAdresses {
-                status: ResponseStatus::Success,
-            },
-        )?),
-    });
+        data: Some(to_binary(&HandleAnswer::AcceptAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
 }
 
-pub fn try_receive<S: Storage, A: Api, Q: Querier>(
+pub fn cancel_admin_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    _sender: HumanAddr,
-    from: HumanAddr,
-    amount: Uint128,
-    msg: Binary,
 ) -> StdResult<HandleResponse> {
-    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
-    let msg: HandleReceiveMsg = from_binary(&msg)?;
-    if let HandleReceiveMsg::MintNfts { count } = msg.clone() {
-        if env.message.sender != config.token_contract.contract_addr {
-            return Err(StdError::generic_err(format!("Invalid token sent!")));
-        } else {
-            return mint_nfts(deps, env.clone(), amount, from, count);
-        }
-    } else {
-        return Err(StdError::generic_err(format!("Receive handler not found!")));
-    }
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(&deps.storage, &config, &env.message.sender, Role::Admin)?;
+
+    deps.storage.remove(PENDING_ADMIN_KEY);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CancelAdminProposal {
+            status: ResponseStatus::Success,
+        })?),
+    })
 }
 
-pub fn mint_nfts<S: Storage, A: Api, Q: Querier>(
+pub fn set_contract_status<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    amount: Uint128,
-    from: HumanAddr,
-    mint_count: u16,
+    status: ContractStatus,
 ) -> StdResult<HandleResponse> {
-    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
 
-    let nft_contract = if let Some(nft_contract) = config.nft_contract.clone() {
-        nft_contract
-    } else {
-        return Err(StdError::generic_err(format!("No NFT contract set")));
-    };
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
 
-    // Checks how many tokens are left
-    let mut count: u16 = load(&deps.storage, COUNT_KEY)?;
+    save(&mut deps.storage, CONTRACT_STATUS_KEY, &status)?;
 
-    if count == 0 {
-        return Err(StdError::generic_err("All tokens have been minted"));
-    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetContractStatus {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
 
-    if count.checked_sub(mint_count) == None {
+/// Fails if every handle is stopped or if `paused` is true for the current status
+fn check_status<S: ReadonlyStorage>(
+    storage: &S,
+    paused: fn(&ContractStatus) -> bool,
+) -> StdResult<()> {
+    let status: ContractStatus = may_load(storage, CONTRACT_STATUS_KEY)?.unwrap_or_default();
+    if status.stop_all || paused(&status) {
         return Err(StdError::generic_err(
-            "Not enought tokens to be minted by this request!",
+            "The contract admin has temporarily disabled this action",
         ));
     }
 
-    if mint_count > config.max_mint_per_tx {
-        return Err(StdError::generic_err(format!(
-            "Requested mint count is too high, max is {}",
-            config.max_mint_per_tx
-        )));
-    }
+    Ok(())
+}
 
-    if !config.standard_mint_enabled && !config.whitelist_mint_enabled {
-        return Err(StdError::generic_err(format!("Mint is not enabled!")));
-    }
+/// Grants `role` to `address`, or revokes it
+pub fn update_role<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    role: Role,
+    address: HumanAddr,
+    grant: bool,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
 
-    // Check if sent amount is correct
-    let total_amount_expected = config.mint_price.u128() * (mint_count as u128);
+    check_role(&deps.storage, &config, &env.message.sender, Role::Admin)?;
 
-    if total_amount_expected != amount.u128() {
-        return Err(StdError::generic_err(format!(
-            "Incorrect amount of snip20 tokens received {:?} != {:?}",
-            amount.u128(),
-            total_amount_expected
-        )));
+    if role == Role::Admin {
+        return Err(StdError::generic_err(
+            "The admin role can only be transferred with ProposeAdmin",
+        ));
     }
 
-    if config.whitelist_mint_enabled {
-        //Whitelist management
-        //Checks if minter has a whitelist reservation, and removes their reservation after minting
-        let mut white_store = PrefixedStorage::new(PREFIX_WHITELIST, &mut deps.storage);
-
-        let list_check: Option<bool> = may_load(&white_store, env.message.sender.0.as_bytes())?;
+    // game operators set with UpdateChangeMetadataPermitedAdresses lose the role too
+    let mut legacy_revoked = false;
+    if !grant && role == Role::GameOperator {
+        let legacy = &mut config.change_metadata_permited_addresses;
+        let count = legacy.len();
+        legacy.retain(|holder| *holder != address);
+        legacy_revoked = legacy.len() != count;
+        if legacy_revoked {
+            TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
+        }
+    }
 
-        // If addr is on list and hasn't minted
-        if Some(false) == list_check {
-            save(&mut white_store, env.message.sender.0.as_bytes(), &true)?;
-        } else if Some(true) == list_check && !config.standard_mint_enabled {
-            return Err(StdError::generic_err(format!(
-                "Whitelist enabled only, sender address already minted on his token eligible thought the whitelist"
-            )));
-        } else if !config.standard_mint_enabled {
+    let mut role_store = PrefixedStorage::new(PREFIX_ROLES, &mut deps.storage);
+    let mut holders: Vec<HumanAddr> =
+        may_load(&role_store, role.name().as_bytes())?.unwrap_or_default();
+    if grant {
+        if !holders.contains(&address) {
+            holders.push(address);
+        }
+    } else {
+        let count = holders.len();
+        holders.retain(|holder| *holder != address);
+        if holders.len() == count && !legacy_revoked {
             return Err(StdError::generic_err(format!(
-                "Whitelist enabled only, sender address not eligible for minting"
+                "{} is not a {}",
+                address,
+                role.name()
             )));
         }
     }
+    save(&mut role_store, role.name().as_bytes(), &holders)?;
 
-    let mut mints: Vec<Mint> = vec![];
+    let data = if grant {
+        HandleAnswer::GrantRole {
+            status: ResponseStatus::Success,
+        }
+    } else {
+        HandleAnswer::RevokeRole {
+            status: ResponseStatus::Success,
+        }
+    };
 
-    let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&data)?),
+    })
+}
 
-    for index in 1..=mint_count {
-        let random_seed = new_entropy(
-            &env,
-            prng_seed.as_ref(),
-            prng_seed.as_ref(),
-            index.to_string().as_bytes(),
-        );
-        let mut rng = ChaChaRng::from_seed(random_seed);
+/// Returns the addresses granted `role`.  The addresses allowed to change metadata with
+/// UpdateChangeMetadataPermitedAdresses are game operators too
+fn role_holders<S: ReadonlyStorage>(
+    storage: &S,
+    config: &Config,
+    role: Role,
+) -> StdResult<Vec<HumanAddr>> {
+    if role == Role::Admin {
+        return Ok(vec![config.admin.clone()]);
+    }
 
-        // Pull random token data for minting then remove from data pool
-        let num = (rng.next_u32() % (count as u32)) as u16 + 1; // an id number between 1 and count
+    let role_store = ReadonlyPrefixedStorage::new(PREFIX_ROLES, storage);
+    let mut holders: Vec<HumanAddr> =
+        may_load(&role_store, role.name().as_bytes())?.unwrap_or_default();
+    if role == Role::GameOperator {
+        for address in config.change_metadata_permited_addresses.iter() {
+            if !holders.contains(address) {
+                holders.push(address.clone());
+            }
+        }
+    }
 
-        let token_data: PreLoad = load(&deps.storage, &num.to_le_bytes())?;
-        let swap_data: PreLoad = load(&deps.storage, &count.to_le_bytes())?;
+    Ok(holders)
+}
 
-        count = count - 1;
+/// Fails unless `address` holds `role`.  The admin holds every role but the game operator one
+fn check_role<S: ReadonlyStorage>(
+    storage: &S,
+    config: &Config,
+    address: &HumanAddr,
+    role: Role,
+) -> StdResult<()> {
+    let admin_allowed = role != Role::GameOperator && *address == config.admin;
+    if admin_allowed || role_holders(storage, config, role)?.contains(address) {
+        return Ok(());
+    }
 
-        save(&mut deps.storage, &num.to_le_bytes(), &swap_data)?;
-        save(&mut deps.storage, COUNT_KEY, &count)?;
+    match role {
+        Role::Admin => Err(StdError::generic_err("Only admin can execute this action!")),
+        Role::GameOperator => Err(StdError::generic_err(
+            "Only game operators can execute this action!",
+        )),
+        _ => Err(StdError::generic_err(format!(
+            "Only admin or a {} can execute this action!",
+            role.name()
+        ))),
+    }
+}
 
-        mints.push(Mint {
-            token_id: Some(token_data.id.clone()),
-            owner: Some(from.clone()),
-            public_metadata: Some(Metadata {
-                extension: Some(Extension {
-                    image: None,
-                    image_data: None,
-                    external_url: None,
-                    description: None,
-                    name: Some("Secret Snail #".to_string() + &token_data.id.to_string()),
-                    attributes: Some(vec![
-                        Trait {
-                            display_type: None,
-                            trait_type: Some("Wins".to_string()),
-                            value: 0.to_string(),
-                            max_value: None,
-                        },
-                        Trait {
-                            display_type: None,
-                            trait_type: Some("Loses".to_string()),
-                            value: 0.to_string(),
-                            max_value: None,
-                        },
-                    ]),
+pub fn add_to_whitelist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    entries: Vec<(HumanAddr, u16)>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    save_whitelist_allowances(&mut deps.storage, &entries)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AddToWhitelist {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn remove_from_whitelist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    addresses: Vec<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    // the removed addresses keep their slot in the index, the queries skip them
+    let mut total: u32 = may_load(&deps.storage, WHITELIST_TOTAL_KEY)?.unwrap_or(0);
+    let mut white_store = PrefixedStorage::new(PREFIX_WHITELIST, &mut deps.storage);
+    for address in addresses.iter() {
+        if may_load::<u16, _>(&white_store, address.0.as_bytes())?.is_some() {
+            white_store.remove(address.0.as_bytes());
+            total = total.saturating_sub(1);
+        }
+    }
+    save(&mut deps.storage, WHITELIST_TOTAL_KEY, &total)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveFromWhitelist {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Saves the whitelist allowances and gives the addresses never whitelisted before a slot in
+/// the whitelist index
+fn save_whitelist_allowances<S: Storage>(
+    storage: &mut S,
+    entries: &[(HumanAddr, u16)],
+) -> StdResult<()> {
+    let mut slots: u32 = may_load(storage, WHITELIST_SLOTS_KEY)?.unwrap_or(0);
+    let mut total: u32 = may_load(storage, WHITELIST_TOTAL_KEY)?.unwrap_or(0);
+
+    for (address, allowance) in entries.iter() {
+        let key = address.0.as_bytes();
+        let slot_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST_SLOT, storage);
+        if may_load::<u32, _>(&slot_store, key)?.is_none() {
+            let mut slot_store = PrefixedStorage::new(PREFIX_WHITELIST_SLOT, storage);
+            save(&mut slot_store, key, &slots)?;
+            let mut index_store = PrefixedStorage::new(PREFIX_WHITELIST_INDEX, storage);
+            save(&mut index_store, &slots.to_be_bytes(), address)?;
+            slots = slots
+                .checked_add(1)
+                .ok_or_else(|| StdError::generic_err("Whitelist index is full"))?;
+        }
+        let mut white_store = PrefixedStorage::new(PREFIX_WHITELIST, storage);
+        if may_load::<u16, _>(&white_store, key)?.is_none() {
+            total += 1;
+        }
+        save(&mut white_store, key, allowance)?;
+    }
+
+    save(storage, WHITELIST_SLOTS_KEY, &slots)?;
+    save(storage, WHITELIST_TOTAL_KEY, &total)
+}
+
+pub fn add_payment_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: SecretContract,
+    price: Uint128,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    if token.contract_addr == config.token_contract.contract_addr {
+        return Err(StdError::generic_err(
+            "The token contract is already accepted at the mint price",
+        ));
+    }
+
+    let mut payment_tokens: Vec<PaymentToken> =
+        may_load(&deps.storage, PAYMENT_TOKENS_KEY)?.unwrap_or_default();
+    let mut messages = vec![];
+
+    match payment_tokens
+        .iter_mut()
+        .find(|accepted| accepted.contract.contract_addr == token.contract_addr)
+    {
+        Some(accepted) => accepted.price = price,
+        None => {
+            messages.push(register_receive_msg(
+                env.contract_code_hash.clone(),
+                None,
+                1,
+                token.token_code_hash.clone(),
+                token.contract_addr.clone(),
+            )?);
+            payment_tokens.push(PaymentToken {
+                contract: token,
+                price,
+            });
+        }
+    }
+
+    save(&mut deps.storage, PAYMENT_TOKENS_KEY, &payment_tokens)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AddPaymentToken {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn remove_payment_token<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    contract_addr: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    let mut payment_tokens: Vec<PaymentToken> =
+        may_load(&deps.storage, PAYMENT_TOKENS_KEY)?.unwrap_or_default();
+    let count = payment_tokens.len();
+    payment_tokens.retain(|token| token.contract.contract_addr != contract_addr);
+    if payment_tokens.len() == count {
+        return Err(StdError::generic_err(format!(
+            "{} is not an accepted payment token",
+            contract_addr
+        )));
+    }
+
+    save(&mut deps.storage, PAYMENT_TOKENS_KEY, &payment_tokens)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemovePaymentToken {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn revoke_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    permit_name: String,
+) -> StdResult<HandleResponse> {
+    RevokedPermits::revoke_permit(
+        &mut deps.storage,
+        PREFIX_REVOKED_PERMITS,
+        &env.message.sender,
+        &permit_name,
+    );
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RevokePermit {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn set_whitelist_merkle_root<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    root: Option<Binary>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    set_merkle_root(&mut deps.storage, root)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetWhitelistMerkleRoot {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+fn set_merkle_root<S: Storage>(storage: &mut S, root: Option<Binary>) -> StdResult<()> {
+    match root {
+        Some(root) => {
+            if root.len() != 32 {
+                return Err(StdError::generic_err(
+                    "Whitelist merkle root must be a 32 bytes sha256 hash",
+                ));
+            }
+            save(storage, WHITELIST_MERKLE_ROOT_KEY, &root.0)
+        }
+        None => {
+            storage.remove(WHITELIST_MERKLE_ROOT_KEY);
+            Ok(())
+        }
+    }
+}
+
+pub fn update_change_metadata_permited_addresses<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    change_metadata_permited_addresses: Vec<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(&deps.storage, &config, &env.message.sender, Role::Admin)?;
+
+    config.change_metadata_permited_addresses = change_metadata_permited_addresses;
+
+    TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
+
+    return Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &HandleAnswer::UpdateChangeMetadataPermitedAdresses {
+                status: ResponseStatus::Success,
+            },
+        )?),
+    });
+}
+
+/// Lets the mint operators replace the mint schedule.  Phases sent again under the same name keep
+/// their wallet counts and minted total, so their limits hold when only the price or times change
+pub fn set_mint_phases<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    mut phases: Vec<MintPhase>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    validate_mint_phases(&mut phases)?;
+
+    let previous_phases: Vec<StoredMintPhase> =
+        may_load(&deps.storage, MINT_PHASES_KEY)?.unwrap_or_default();
+    let mut last_id: u32 = may_load(&deps.storage, MINT_PHASES_COUNT_KEY)?.unwrap_or(0);
+    let mut stored_phases = vec![];
+
+    for phase in phases {
+        let phase_id = match previous_phases
+            .iter()
+            .find(|previous| previous.name == phase.name)
+        {
+            Some(previous) => previous.id,
+            None => {
+                last_id += 1;
+                last_id
+            }
+        };
+        last_id += 1;
+        let allowed_list = last_id;
+
+        if let Some(allowed_addresses) = &phase.allowed_addresses {
+            let mut allowed_store = PrefixedStorage::multilevel(
+                &[PREFIX_PHASE_ALLOWED, &allowed_list.to_le_bytes()],
+                &mut deps.storage,
+            );
+            for address in allowed_addresses {
+                save(&mut allowed_store, address.0.as_bytes(), &true)?;
+            }
+        }
+
+        stored_phases.push(StoredMintPhase {
+            id: phase_id,
+            allowed_list,
+            name: phase.name,
+            start_time: phase.start_time,
+            end_time: phase.end_time,
+            price: phase.price,
+            max_per_wallet: phase.max_per_wallet,
+            max_per_tx: phase.max_per_tx,
+            restricted: phase.allowed_addresses.is_some(),
+        });
+    }
+
+    if !stored_phases.is_empty() {
+        save(&mut deps.storage, METADATA_LOCKED_KEY, &true)?;
+    }
+    save(&mut deps.storage, MINT_PHASES_COUNT_KEY, &last_id)?;
+    save(&mut deps.storage, MINT_PHASES_KEY, &stored_phases)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMintPhases {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn set_max_mint_per_wallet<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    max_mint_per_wallet: Option<u16>,
+) -> StdResult<HandleResponse> {
+    let mut config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    config.max_mint_per_wallet = max_mint_per_wallet;
+    TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMaxMintPerWallet {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn set_dutch_auction<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    auction: Option<DutchAuction>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::MintOperator,
+    )?;
+
+    // the claims are computed from the auction the purchases were made in
+    let unclaimed: u32 = may_load(&deps.storage, AUCTION_UNCLAIMED_KEY)?.unwrap_or(0);
+    if unclaimed > 0 {
+        return Err(StdError::generic_err(format!(
+            "The dutch auction can't be changed while {} purchases have rebates to claim",
+            unclaimed
+        )));
+    }
+
+    match auction {
+        Some(auction) => {
+            if auction.step_duration == 0 {
+                return Err(StdError::generic_err(
+                    "Dutch auction steps need a duration of at least 1 second",
+                ));
+            }
+            if auction.end_time <= auction.start_time {
+                return Err(StdError::generic_err(
+                    "Dutch auction has to end after it starts",
+                ));
+            }
+            if auction.floor_price > auction.start_price {
+                return Err(StdError::generic_err(
+                    "Dutch auction floor price can't be higher than its start price",
+                ));
+            }
+            save(&mut deps.storage, DUTCH_AUCTION_KEY, &auction)?;
+        }
+        None => deps.storage.remove(DUTCH_AUCTION_KEY),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetDutchAuction {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn claim_auction_rebate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let auction: DutchAuction = may_load(&deps.storage, DUTCH_AUCTION_KEY)?
+        .ok_or_else(|| StdError::generic_err("No dutch auction set"))?;
+
+    if !auction.rebate {
+        return Err(StdError::generic_err("The dutch auction has no rebates"));
+    }
+
+    let count = pool_count(&deps.storage)?;
+    let reserved = reserved_count(&deps.storage)?;
+    let sold_out = count == reserved;
+
+    if !sold_out && env.block.time < auction.end_time {
+        return Err(StdError::generic_err("The dutch auction is not over yet"));
+    }
+
+    let clearing_price = if sold_out {
+        may_load(&deps.storage, LAST_AUCTION_PRICE_KEY)?.unwrap_or(auction.floor_price)
+    } else {
+        auction.floor_price
+    };
+
+    // auction mints could be paid with the token contract or the native coin
+    let mut funds = vec![Payment::Snip20 {
+        token_contract: config.token_contract,
+        amount: Uint128::zero(),
+        price: None,
+    }];
+    if let Some(denom) = config.native_denom {
+        funds.push(Payment::Native {
+            denom,
+            amount: Uint128::zero(),
+        });
+    }
+
+    let mut messages = vec![];
+    let mut logs = vec![];
+    let mut claimed = false;
+    for payment in funds.iter() {
+        let mut purchases_store = PrefixedStorage::multilevel(
+            &[PREFIX_AUCTION_PURCHASES, &payment.funds_key()],
+            &mut deps.storage,
+        );
+        let purchase: Option<AuctionPurchase> =
+            may_load(&purchases_store, env.message.sender.0.as_bytes())?;
+        if let Some(purchase) = purchase {
+            purchases_store.remove(env.message.sender.0.as_bytes());
+            claimed = true;
+            let unclaimed: u32 = may_load(&deps.storage, AUCTION_UNCLAIMED_KEY)?.unwrap_or(0);
+            save(
+                &mut deps.storage,
+                AUCTION_UNCLAIMED_KEY,
+                &unclaimed.saturating_sub(1),
+            )?;
+
+            // the contract kept everything paid over the floor price until now
+            let overflow = || StdError::generic_err("Dutch auction purchase price overflow");
+            let cleared = clearing_price
+                .u128()
+                .checked_mul(purchase.count as u128)
+                .ok_or_else(overflow)?
+                .min(purchase.paid.u128());
+            let rebate = purchase.paid.u128() - cleared;
+            let floor = auction
+                .floor_price
+                .u128()
+                .checked_mul(purchase.count as u128)
+                .ok_or_else(overflow)?;
+            let revenue = cleared.saturating_sub(floor);
+            let owed = auction_owed(&deps.storage, payment)?
+                .checked_sub(rebate + revenue)
+                .ok_or_else(|| {
+                    StdError::generic_err("Dutch auction rebates exceed the funds kept for them")
+                })?;
+            save_auction_owed(&mut deps.storage, payment, owed)?;
+
+            if rebate > 0 {
+                messages.push(payment.send_msg(
+                    &env.contract.address,
+                    &env.message.sender,
+                    Uint128(rebate),
+                )?);
+                logs.push(log("rebate", rebate));
+            }
+            messages.extend(revenue_split_msgs(
+                &config.revenue_split,
+                payment,
+                Uint128(revenue),
+                &env.contract.address,
+            )?);
+        }
+    }
+
+    if !claimed {
+        return Err(StdError::generic_err(
+            "No dutch auction mints to claim a rebate for",
+        ));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::ClaimAuctionRebate {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Funds kept for the dutch auction purchases paid with `payment` that were not claimed yet
+fn auction_owed<S: ReadonlyStorage>(storage: &S, payment: &Payment) -> StdResult<u128> {
+    let owed_store = ReadonlyPrefixedStorage::new(PREFIX_AUCTION_OWED, storage);
+    let owed: Option<Uint128> = may_load(&owed_store, &payment.funds_key())?;
+    Ok(owed.map_or(0, |owed| owed.u128()))
+}
+
+fn save_auction_owed<S: Storage>(storage: &mut S, payment: &Payment, owed: u128) -> StdResult<()> {
+    let mut owed_store = PrefixedStorage::new(PREFIX_AUCTION_OWED, storage);
+    save(&mut owed_store, &payment.funds_key(), &Uint128(owed))
+}
+
+/// Balance of the minter in the funds of `payment`.  SNIP-20 balances are queried with the
+/// viewing key set by SetTokenViewingKey
+fn funds_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    my_address: &HumanAddr,
+    payment: &Payment,
+) -> StdResult<u128> {
+    match payment {
+        Payment::Snip20 { token_contract, .. } => {
+            let viewing_keys: Vec<(SecretContract, String)> =
+                may_load(&deps.storage, TOKEN_VIEWING_KEYS_KEY)?.unwrap_or_default();
+            let key = viewing_keys
+                .into_iter()
+                .find(|(token, _)| token.contract_addr == token_contract.contract_addr)
+                .map(|(_, key)| key)
+                .ok_or_else(|| {
+                    StdError::generic_err(
+                        "Set a viewing key on the token with SetTokenViewingKey to withdraw it while dutch auction rebates are unclaimed",
+                    )
+                })?;
+            let balance = balance_query(
+                &deps.querier,
+                my_address.clone(),
+                key,
+                BLOCK_SIZE,
+                token_contract.token_code_hash.clone(),
+                token_contract.contract_addr.clone(),
+            )?;
+            Ok(balance.amount.u128())
+        }
+        Payment::Native { denom, .. } => {
+            Ok(deps.querier.query_balance(my_address, denom)?.amount.u128())
+        }
+    }
+}
+
+/// Sorts the phases by start time and checks they make sense and never overlap, so at most one
+/// phase is active at any time
+fn validate_mint_phases(phases: &mut Vec<MintPhase>) -> StdResult<()> {
+    phases.sort_by_key(|p| p.start_time);
+
+    let mut names: Vec<&str> = phases.iter().map(|p| p.name.as_str()).collect();
+    names.sort_unstable();
+    names.dedup();
+    if names.len() != phases.len() {
+        return Err(StdError::generic_err("Mint phase names must be unique"));
+    }
+
+    for phase in phases.iter() {
+        if phase.max_per_tx == 0 {
+            return Err(StdError::generic_err(format!(
+                "Mint phase {} needs a max per tx of at least 1",
+                phase.name
+            )));
+        }
+
+        if phase.end_time.map_or(false, |end| end <= phase.start_time) {
+            return Err(StdError::generic_err(format!(
+                "Mint phase {} has to end after it starts",
+                phase.name
+            )));
+        }
+    }
+
+    for pair in phases.windows(2) {
+        if pair[0]
+            .end_time
+            .map_or(true, |end| end > pair[1].start_time)
+        {
+            return Err(StdError::generic_err(format!(
+                "Mint phases {} and {} overlap",
+                pair[0].name, pair[1].name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Lets the addresses with authority to update metadata add race results to a snail's public
+/// Wins/Loses traits
+pub fn update_snail_stats<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token_id: String,
+    wins_delta: u32,
+    loses_delta: u32,
+) -> StdResult<HandleResponse> {
+    check_status(&deps.storage, |status| status.game_paused)?;
+
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    check_role(
+        &deps.storage,
+        &config,
+        &env.message.sender,
+        Role::GameOperator,
+    )?;
+
+    let nft_contract = if let Some(nft_contract) = config.nft_contract {
+        nft_contract
+    } else {
+        return Err(StdError::generic_err("No NFT contract set"));
+    };
+
+    let NftsQueryAnswer::NftInfo {
+        token_uri,
+        extension,
+    } = NftsQueryMsg::NftInfo {
+        token_id: token_id.clone(),
+    }
+    .query(
+        &deps.querier,
+        nft_contract.token_code_hash.clone(),
+        nft_contract.contract_addr.clone(),
+    )?;
+
+    // a token without on-chain metadata was not minted by this contract (or does not exist)
+    let mut extension = if let Some(extension) = extension {
+        extension
+    } else {
+        return Err(StdError::generic_err(format!(
+            "Token {} has no snail stats to update",
+            token_id
+        )));
+    };
+
+    let mut attributes = extension.attributes.unwrap_or_default();
+    add_to_trait(&mut attributes, WINS_TRAIT, wins_delta)?;
+    add_to_trait(&mut attributes, LOSES_TRAIT, loses_delta)?;
+    extension.attributes = Some(attributes);
+
+    let set_metadata_msg = NftsHandleMsg::SetMetadata {
+        token_id,
+        public_metadata: Some(Metadata {
+            token_uri,
+            extension: Some(extension),
+        }),
+        private_metadata: None,
+        padding: None,
+    }
+    .to_cosmos_msg(
+        nft_contract.token_code_hash,
+        nft_contract.contract_addr,
+        None,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![set_metadata_msg],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::UpdateSnailStats {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Adds `delta` to the numeric value of the trait with the given type, adding the trait if the
+/// token does not have it yet
+fn add_to_trait(attributes: &mut Vec<Trait>, trait_type: &str, delta: u32) -> StdResult<()> {
+    let position = attributes
+        .iter()
+        .position(|t| t.trait_type.as_deref() == Some(trait_type));
+
+    let attribute = if let Some(position) = position {
+        &mut attributes[position]
+    } else {
+        attributes.push(Trait {
+            display_type: None,
+            trait_type: Some(trait_type.to_string()),
+            value: 0.to_string(),
+            max_value: None,
+        });
+        attributes.last_mut().unwrap()
+    };
+
+    let current: u32 = attribute.value.parse().map_err(|_| {
+        StdError::generic_err(format!(
+            "{} trait has a non numeric value: {}",
+            trait_type, attribute.value
+        ))
+    })?;
+
+    let updated = current
+        .checked_add(delta)
+        .ok_or_else(|| StdError::generic_err(format!("{} trait overflow", trait_type)))?;
+
+    attribute.value = updated.to_string();
+
+    Ok(())
+}
+
+pub fn try_receive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    _sender: HumanAddr,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Binary,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let payment = if env.message.sender == config.token_contract.contract_addr {
+        Payment::Snip20 {
+            token_contract: config.token_contract,
+            amount,
+            price: None,
+        }
+    } else {
+        let payment_tokens: Vec<PaymentToken> =
+            may_load(&deps.storage, PAYMENT_TOKENS_KEY)?.unwrap_or_default();
+        match payment_tokens
+            .into_iter()
+            .find(|token| token.contract.contract_addr == env.message.sender)
+        {
+            Some(token) => Payment::Snip20 {
+                token_contract: token.contract,
+                amount,
+                price: Some(token.price),
+            },
+            None => return Err(StdError::generic_err("Invalid token sent!")),
+        }
+    };
+
+    // tokens sent while minting is stopped go straight back to their owner
+    if check_status(&deps.storage, |status| status.mint_paused).is_err() {
+        return Ok(HandleResponse {
+            messages: vec![payment.send_msg(&env.contract.address, &from, amount)?],
+            log: vec![log("refund", amount)],
+            data: Some(to_binary(&HandleAnswer::MintNfts {
+                status: ResponseStatus::Failure,
+            })?),
+        });
+    }
+
+    let msg: HandleReceiveMsg = from_binary(&msg)?;
+    if let HandleReceiveMsg::MintNfts {
+        count,
+        entropy,
+        whitelist_proof,
+        partial_fill,
+    } = msg.clone()
+    {
+        let request = MintRequest {
+            count,
+            entropy,
+            whitelist_proof,
+            partial_fill: partial_fill.unwrap_or(false),
+        };
+        // the buyer is the owner of the tokens sent, `sender` may only be allowed to send them
+        return mint_nfts(deps, env.clone(), payment, from, request);
+    } else {
+        return Err(StdError::generic_err(format!("Receive handler not found!")));
+    }
+}
+
+/// What a buyer asks for in a mint, whatever it pays with
+pub struct MintRequest {
+    pub count: u16,
+    pub entropy: Option<String>,
+    pub whitelist_proof: Option<WhitelistProof>,
+    pub partial_fill: bool,
+}
+
+pub fn try_mint_native<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    request: MintRequest,
+) -> StdResult<HandleResponse> {
+    check_status(&deps.storage, |status| status.mint_paused)?;
+
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let denom = config
+        .native_denom
+        .ok_or_else(|| StdError::generic_err("Native mint is not enabled"))?;
+
+    let mut amount = 0u128;
+    for coin in env.message.sent_funds.iter() {
+        if coin.denom != denom {
+            return Err(StdError::generic_err(format!(
+                "Only {} can be sent to mint",
+                denom
+            )));
+        }
+        amount = amount
+            .checked_add(coin.amount.u128())
+            .ok_or_else(|| StdError::generic_err("Sent funds overflow"))?;
+    }
+
+    let payment = Payment::Native {
+        denom,
+        amount: Uint128(amount),
+    };
+    let buyer = env.message.sender.clone();
+    mint_nfts(deps, env, payment, buyer, request)
+}
+
+/// Mints for `buyer`, the address paying for the mint.  The whitelist, the wallet caps, the
+/// phases, the auction rebates, the minted tokens and the refunds all go by it, never by the
+/// message sender, which is the token contract when paying with a SNIP-20
+pub fn mint_nfts<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    payment: Payment,
+    buyer: HumanAddr,
+    request: MintRequest,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let MintRequest {
+        count: mut mint_count,
+        entropy,
+        whitelist_proof,
+        partial_fill,
+    } = request;
+
+    if mint_count == 0 {
+        return Err(StdError::generic_err("At least one token must be minted"));
+    }
+
+    let nft_contract = if let Some(nft_contract) = config.nft_contract.clone() {
+        nft_contract
+    } else {
+        return Err(StdError::generic_err(format!("No NFT contract set")));
+    };
+
+    // Checks how many tokens are left, tokens reserved by pending mints are already sold
+    let count = pool_count(&deps.storage)?;
+    let reserved = reserved_count(&deps.storage)?;
+    let available = count.saturating_sub(reserved);
+
+    if available == 0 {
+        return Err(StdError::generic_err("All tokens have been minted"));
+    }
+    // a single mint never asks for more than u16::MAX tokens
+    let available = available.min(u16::MAX as u32) as u16;
+
+    mint_count = fit_mint_count(mint_count, available, partial_fill, || {
+        StdError::generic_err("Not enought tokens to be minted by this request!")
+    })?;
+
+    let wallet_store = ReadonlyPrefixedStorage::new(PREFIX_WALLET_MINTED, &deps.storage);
+    let wallet_minted: u16 = may_load(&wallet_store, buyer.0.as_bytes())?.unwrap_or(0);
+    if let Some(max_mint_per_wallet) = config.max_mint_per_wallet {
+        let left = max_mint_per_wallet.saturating_sub(wallet_minted);
+        mint_count = fit_mint_count(mint_count, left, partial_fill, || {
+            StdError::generic_err(format!(
+                "Mint limit per wallet reached, {} left for this address",
+                left
+            ))
+        })?;
+    }
+
+    let phases: Vec<StoredMintPhase> =
+        may_load(&deps.storage, MINT_PHASES_KEY)?.unwrap_or_default();
+
+    let phase = if phases.is_empty() {
+        mint_count = fit_mint_count(mint_count, config.max_mint_per_tx, partial_fill, || {
+            StdError::generic_err(format!(
+                "Requested mint count is too high, max is {}",
+                config.max_mint_per_tx
+            ))
+        })?;
+
+        if !config.standard_mint_enabled && !config.whitelist_mint_enabled {
+            return Err(StdError::generic_err(format!("Mint is not enabled!")));
+        }
+
+        None
+    } else {
+        let phase = active_phase(&phases, env.block.time)
+            .ok_or_else(|| StdError::generic_err("No mint phase is active"))?;
+        mint_count = phase_mint_count(&deps.storage, phase, &buyer, mint_count, partial_fill)?;
+        Some(phase)
+    };
+
+    //Whitelist management
+    //Checks how many tokens the buyer can still mint through the whitelist, the minted tokens
+    //are taken from their allowance once the mint is paid.  Merkle whitelist allowances are
+    //tracked by leaf
+    let mut whitelist_slot = None;
+    if phase.is_none() && config.whitelist_mint_enabled {
+        let (prefix, key, allowance) = if let Some(whitelist_proof) = whitelist_proof {
+            let leaf = verify_whitelist_proof(&deps.storage, &buyer, &whitelist_proof)?;
+            let leaves_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST_LEAVES, &deps.storage);
+            let allowance: u16 =
+                may_load(&leaves_store, &leaf)?.unwrap_or(whitelist_proof.allowance);
+            (PREFIX_WHITELIST_LEAVES, leaf, Some(allowance))
+        } else {
+            let white_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST, &deps.storage);
+            let allowance: Option<u16> = may_load(&white_store, buyer.0.as_bytes())?;
+            (PREFIX_WHITELIST, buyer.0.as_bytes().to_vec(), allowance)
+        };
+
+        // with the standard mint open the whitelist allowance is used up first, but doesn't
+        // limit the mint
+        if !config.standard_mint_enabled {
+            let allowance = allowance.ok_or_else(|| {
+                StdError::generic_err(
+                    "Whitelist enabled only, buyer address not eligible for minting",
+                )
+            })?;
+            mint_count = fit_mint_count(mint_count, allowance, partial_fill, || {
+                StdError::generic_err(format!(
+                    "Whitelist enabled only, address can only mint {} more tokens thought the whitelist",
+                    allowance
+                ))
+            })?;
+        }
+
+        whitelist_slot = allowance.map(|allowance| (prefix, key, allowance));
+    }
+
+    // payment tokens with their own price are not sold in the dutch auction
+    let auction: Option<DutchAuction> = match payment.price() {
+        Some(_) => None,
+        None => may_load(&deps.storage, DUTCH_AUCTION_KEY)?,
+    };
+    let mint_price = payment
+        .price()
+        .or_else(|| auction.as_ref().map(|a| a.price_at(env.block.time)))
+        .unwrap_or_else(|| phase.map_or(config.mint_price, |phase| phase.price))
+        .u128();
+    let amount = payment.amount().u128();
+    let price_overflow = || StdError::generic_err("Mint price overflow");
+    let incorrect_amount = |mint_count: u16| match mint_price.checked_mul(mint_count as u128) {
+        Some(expected) => StdError::generic_err(format!(
+            "Incorrect amount of {} received {:?} != {:?}",
+            payment.name(),
+            amount,
+            expected
+        )),
+        None => price_overflow(),
+    };
+
+    // Check if sent amount is correct, partial fills mint as many tokens as they can pay
+    if partial_fill && mint_price > 0 {
+        let affordable = (amount / mint_price).min(mint_count as u128) as u16;
+        mint_count = fit_mint_count(mint_count, affordable, partial_fill, || {
+            incorrect_amount(mint_count)
+        })?;
+    }
+
+    let total_amount_expected = mint_price
+        .checked_mul(mint_count as u128)
+        .ok_or_else(price_overflow)?;
+
+    if total_amount_expected > amount || (!partial_fill && total_amount_expected != amount) {
+        return Err(incorrect_amount(mint_count));
+    }
+
+    let mut wallet_store = PrefixedStorage::new(PREFIX_WALLET_MINTED, &mut deps.storage);
+    save(
+        &mut wallet_store,
+        buyer.0.as_bytes(),
+        &wallet_minted.saturating_add(mint_count),
+    )?;
+
+    if let Some(phase) = phase {
+        record_phase_mint(&mut deps.storage, phase, &buyer, mint_count)?;
+    }
+
+    if let Some((prefix, key, allowance)) = whitelist_slot {
+        let mut white_store = PrefixedStorage::new(prefix, &mut deps.storage);
+        save(
+            &mut white_store,
+            &key,
+            &allowance.saturating_sub(mint_count),
+        )?;
+    }
+
+    // with rebates only the floor price is paid out now, the rest waits for the clearing price
+    let mut revenue = total_amount_expected;
+    if let Some(auction) = auction.filter(|auction| auction.rebate) {
+        revenue = auction
+            .floor_price
+            .u128()
+            .checked_mul(mint_count as u128)
+            .ok_or_else(price_overflow)?;
+        // what is paid over the floor price is kept until the buyer claims it
+        let owed = auction_owed(&deps.storage, &payment)?;
+        let owed = total_amount_expected
+            .checked_sub(revenue)
+            .and_then(|kept| owed.checked_add(kept))
+            .ok_or_else(|| StdError::generic_err("Dutch auction rebates overflow"))?;
+
+        save(
+            &mut deps.storage,
+            LAST_AUCTION_PRICE_KEY,
+            &Uint128(mint_price),
+        )?;
+
+        let mut purchases_store = PrefixedStorage::multilevel(
+            &[PREFIX_AUCTION_PURCHASES, &payment.funds_key()],
+            &mut deps.storage,
+        );
+        let previous: Option<AuctionPurchase> = may_load(&purchases_store, buyer.0.as_bytes())?;
+        let is_new = previous.is_none();
+        let mut purchase = previous.unwrap_or(AuctionPurchase {
+            paid: Uint128::zero(),
+            count: 0,
+        });
+        purchase.paid = Uint128(
+            purchase
+                .paid
+                .u128()
+                .checked_add(total_amount_expected)
+                .ok_or_else(|| StdError::generic_err("Dutch auction purchase overflow"))?,
+        );
+        purchase.count = purchase.count.saturating_add(mint_count);
+        save(&mut purchases_store, buyer.0.as_bytes(), &purchase)?;
+        if is_new {
+            let unclaimed: u32 = may_load(&deps.storage, AUCTION_UNCLAIMED_KEY)?.unwrap_or(0);
+            save(&mut deps.storage, AUCTION_UNCLAIMED_KEY, &(unclaimed + 1))?;
+        }
+        save_auction_owed(&mut deps.storage, &payment, owed)?;
+    }
+
+    let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
+    let entropy = entropy.unwrap_or_default();
+
+    let mut messages = vec![];
+    let mut logs = vec![];
+
+    if config.commit_reveal_enabled {
+        let mint_id = commit_mint(deps, &env, &prng_seed, buyer.clone(), mint_count, &entropy)?;
+        logs.push(log("pending_mint_id", mint_id));
+    } else {
+        let entropy = [prng_seed.as_slice(), entropy.as_bytes()].concat();
+        let random_seeds = (1..=mint_count)
+            .map(|index| {
+                new_entropy(
+                    &env,
+                    prng_seed.as_ref(),
+                    entropy.as_ref(),
+                    index.to_string().as_bytes(),
+                )
+            })
+            .collect();
+        let mints = draw_mints(
+            &mut deps.storage,
+            &buyer,
+            random_seeds,
+            config.sealed_metadata,
+        )?;
+        messages.push(batch_mint_msg(&nft_contract, mints)?);
+    }
+
+    messages.extend(revenue_split_msgs(
+        &config.revenue_split,
+        &payment,
+        Uint128(revenue),
+        &env.contract.address,
+    )?);
+
+    let refund = amount - total_amount_expected;
+    if refund > 0 {
+        messages.push(payment.send_msg(&env.contract.address, &buyer, Uint128(refund))?);
+        logs.push(log("refund", refund));
+    }
+
+    return Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(&HandleAnswer::MintNfts {
+            status: ResponseStatus::Success,
+        })?),
+    });
+}
+
+/// Checks `whitelist_proof` against the stored merkle root and returns the proven leaf
+fn verify_whitelist_proof<S: ReadonlyStorage>(
+    storage: &S,
+    address: &HumanAddr,
+    whitelist_proof: &WhitelistProof,
+) -> StdResult<Vec<u8>> {
+    let root: Vec<u8> = may_load(storage, WHITELIST_MERKLE_ROOT_KEY)?
+        .ok_or_else(|| StdError::generic_err("No whitelist merkle root set"))?;
+
+    let leaf = whitelist_leaf(address, whitelist_proof.allowance);
+    let computed_root = whitelist_proof
+        .proof
+        .iter()
+        .fold(leaf.clone(), |node, sibling| {
+            if node.as_slice() <= sibling.as_slice() {
+                Sha256::digest(&[node.as_slice(), sibling.as_slice()].concat()).to_vec()
+            } else {
+                Sha256::digest(&[sibling.as_slice(), node.as_slice()].concat()).to_vec()
+            }
+        });
+
+    if computed_root != root {
+        return Err(StdError::generic_err("Invalid whitelist proof"));
+    }
+
+    Ok(leaf)
+}
+
+/// Returns the merkle whitelist leaf of an address and its allowance
+pub fn whitelist_leaf(address: &HumanAddr, allowance: u16) -> Vec<u8> {
+    Sha256::digest(&[address.0.as_bytes(), &allowance.to_be_bytes()].concat()).to_vec()
+}
+
+/// Returns the phase running at `time`, if any
+fn active_phase(phases: &[StoredMintPhase], time: u64) -> Option<&StoredMintPhase> {
+    phases
+        .iter()
+        .find(|p| p.start_time <= time && p.end_time.map_or(true, |end| time < end))
+}
+
+/// Returns `mint_count` if it is within `limit`.  Otherwise partial fills are cut down to the
+/// limit and other mints fail with `error`
+fn fit_mint_count<F: FnOnce() -> StdError>(
+    mint_count: u16,
+    limit: u16,
+    partial_fill: bool,
+    error: F,
+) -> StdResult<u16> {
+    if mint_count <= limit {
+        Ok(mint_count)
+    } else if partial_fill && limit > 0 {
+        Ok(limit)
+    } else {
+        Err(error())
+    }
+}
+
+/// Checks `buyer` can mint in `phase` and returns how many of the `mint_count` tokens it can mint
+fn phase_mint_count<S: ReadonlyStorage>(
+    storage: &S,
+    phase: &StoredMintPhase,
+    buyer: &HumanAddr,
+    mint_count: u16,
+    partial_fill: bool,
+) -> StdResult<u16> {
+    let mint_count = fit_mint_count(mint_count, phase.max_per_tx, partial_fill, || {
+        StdError::generic_err(format!(
+            "Requested mint count is too high, max is {}",
+            phase.max_per_tx
+        ))
+    })?;
+
+    let phase_id = phase.id.to_le_bytes();
+
+    if phase.restricted {
+        let allowed_store = ReadonlyPrefixedStorage::multilevel(
+            &[PREFIX_PHASE_ALLOWED, &phase.allowed_list.to_le_bytes()],
+            storage,
+        );
+        let allowed: Option<bool> = may_load(&allowed_store, buyer.0.as_bytes())?;
+        if allowed.is_none() {
+            return Err(StdError::generic_err(format!(
+                "Address not allowed to mint in the {} phase",
+                phase.name
+            )));
+        }
+    }
+
+    match phase.max_per_wallet {
+        Some(max_per_wallet) => {
+            let minted_store =
+                ReadonlyPrefixedStorage::multilevel(&[PREFIX_PHASE_MINTED, &phase_id], storage);
+            let minted: u16 = may_load(&minted_store, buyer.0.as_bytes())?.unwrap_or(0);
+            let left = max_per_wallet.saturating_sub(minted);
+            fit_mint_count(mint_count, left, partial_fill, || {
+                StdError::generic_err(format!(
+                    "Mint limit of the {} phase reached, {} left for this address",
+                    phase.name, left
+                ))
+            })
+        }
+        None => Ok(mint_count),
+    }
+}
+
+/// Counts the tokens minted by `buyer` in `phase` against its wallet cap and adds them to the
+/// phase total
+fn record_phase_mint<S: Storage>(
+    storage: &mut S,
+    phase: &StoredMintPhase,
+    buyer: &HumanAddr,
+    mint_count: u16,
+) -> StdResult<()> {
+    let phase_id = phase.id.to_le_bytes();
+    let mut total_store = PrefixedStorage::new(PREFIX_PHASE_TOTAL_MINTED, storage);
+    let total: u32 = may_load(&total_store, &phase_id)?.unwrap_or(0);
+    save(
+        &mut total_store,
+        &phase_id,
+        &total.saturating_add(mint_count as u32),
+    )?;
+
+    if phase.max_per_wallet.is_some() {
+        let phase_id = phase.id.to_le_bytes();
+        let mut minted_store =
+            PrefixedStorage::multilevel(&[PREFIX_PHASE_MINTED, &phase_id], storage);
+        let minted: u16 = may_load(&minted_store, buyer.0.as_bytes())?.unwrap_or(0);
+        save(
+            &mut minted_store,
+            buyer.0.as_bytes(),
+            &minted.saturating_add(mint_count),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Reserves `count` tokens for `owner` without picking them, so nobody can know which tokens
+/// they get until the mint is revealed in a later block.  Returns the id of the pending mint
+fn commit_mint<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    prng_seed: &[u8],
+    owner: HumanAddr,
+    count: u16,
+    entropy: &str,
+) -> StdResult<u32> {
+    let mint_id: u32 = may_load(&deps.storage, PENDING_MINTS_COUNT_KEY)?.unwrap_or(0) + 1;
+    let mint_entropy = new_entropy(env, prng_seed, entropy.as_bytes(), &mint_id.to_le_bytes());
+
+    // every commit stirs the pool used by the reveals, so the entropy a mint is revealed with
+    // depends on commits its minter can't foresee
+    let pool: Vec<u8> =
+        may_load(&deps.storage, ENTROPY_POOL_KEY)?.unwrap_or_else(|| prng_seed.to_vec());
+    let pool = sha_256(&[pool.as_slice(), &mint_entropy].concat()).to_vec();
+    save(&mut deps.storage, ENTROPY_POOL_KEY, &pool)?;
+
+    let reserved = reserved_count(&deps.storage)?
+        .checked_add(count as u32)
+        .ok_or_else(|| StdError::generic_err("Too many tokens reserved"))?;
+    save(&mut deps.storage, RESERVED_KEY, &reserved)?;
+
+    let mut pending_ids: Vec<u32> =
+        may_load(&deps.storage, PENDING_MINT_IDS_KEY)?.unwrap_or_default();
+    pending_ids.push(mint_id);
+    save(&mut deps.storage, PENDING_MINT_IDS_KEY, &pending_ids)?;
+    save(&mut deps.storage, PENDING_MINTS_COUNT_KEY, &mint_id)?;
+
+    let mut pending_store = PrefixedStorage::new(PREFIX_PENDING_MINTS, &mut deps.storage);
+    save(
+        &mut pending_store,
+        &mint_id.to_le_bytes(),
+        &PendingMint {
+            owner,
+            count,
+            commit_height: env.block.height,
+            entropy: mint_entropy.to_vec(),
+        },
+    )?;
+
+    Ok(mint_id)
+}
+
+/// Picks and mints the tokens reserved by commit-reveal mints.  Anyone can reveal a mint, the
+/// tokens always go to the address that paid for them
+pub fn reveal_mints<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    mint_ids: Vec<u32>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    let nft_contract = if let Some(nft_contract) = config.nft_contract {
+        nft_contract
+    } else {
+        return Err(StdError::generic_err("No NFT contract set"));
+    };
+
+    let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
+    let pool: Vec<u8> = may_load(&deps.storage, ENTROPY_POOL_KEY)?.unwrap_or_default();
+    let mut pending_ids: Vec<u32> =
+        may_load(&deps.storage, PENDING_MINT_IDS_KEY)?.unwrap_or_default();
+    let mut reserved = reserved_count(&deps.storage)?;
+    let mut messages = vec![];
+
+    for mint_id in mint_ids {
+        let pending_store = ReadonlyPrefixedStorage::new(PREFIX_PENDING_MINTS, &deps.storage);
+        let pending: PendingMint = may_load(&pending_store, &mint_id.to_le_bytes())?
+            .ok_or_else(|| StdError::generic_err(format!("Pending mint {} not found", mint_id)))?;
+
+        if env.block.height <= pending.commit_height {
+            return Err(StdError::generic_err(format!(
+                "Mint {} can only be revealed after block {}",
+                mint_id, pending.commit_height
+            )));
+        }
+
+        let random_seeds = (1..=pending.count)
+            .map(|index| reveal_entropy(&prng_seed, &pool, &pending.entropy, index))
+            .collect();
+        let mints = draw_mints(
+            &mut deps.storage,
+            &pending.owner,
+            random_seeds,
+            config.sealed_metadata,
+        )?;
+        messages.push(batch_mint_msg(&nft_contract, mints)?);
+
+        reserved = reserved.saturating_sub(pending.count as u32);
+        pending_ids.retain(|id| *id != mint_id);
+        let mut pending_store = PrefixedStorage::new(PREFIX_PENDING_MINTS, &mut deps.storage);
+        pending_store.remove(&mint_id.to_le_bytes());
+    }
+
+    save(&mut deps.storage, RESERVED_KEY, &reserved)?;
+    save(&mut deps.storage, PENDING_MINT_IDS_KEY, &pending_ids)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RevealMints {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Randomly picks one token from the pool for every seed, removing it from the pool, and
+/// returns the mints giving them to `owner`.  The pool holds the load indexes of the tokens
+/// left, a slot that was never written holds its own index
+fn draw_mints<S: Storage>(
+    storage: &mut S,
+    owner: &HumanAddr,
+    random_seeds: Vec<[u8; 32]>,
+    sealed_metadata: bool,
+) -> StdResult<Vec<Mint>> {
+    let mut count = pool_count(storage)?;
+    if (random_seeds.len() as u64) > count as u64 {
+        return Err(StdError::generic_err(
+            "Not enought tokens to be minted by this request!",
+        ));
+    }
+    let minted: u32 = may_load(storage, MINTED_COUNT_KEY)?.unwrap_or(0);
+    let minted = minted
+        .checked_add(random_seeds.len() as u32)
+        .ok_or_else(|| StdError::generic_err("Minted count overflow"))?;
+    save(storage, MINTED_COUNT_KEY, &minted)?;
+    let mut mints: Vec<Mint> = vec![];
+
+    for random_seed in random_seeds {
+        let mut rng = ChaChaRng::from_seed(random_seed);
+
+        // Pull random token data for minting then remove from data pool
+        let num = rng.next_u32() % count + 1; // an id number between 1 and count
+
+        let mut pool_store = PrefixedStorage::new(PREFIX_POOL, storage);
+        let index = pool_slot(&pool_store, num)?;
+        let last_index = pool_slot(&pool_store, count)?;
+        save(&mut pool_store, &num.to_le_bytes(), &last_index)?;
+
+        count -= 1;
+
+        let preload_store = ReadonlyPrefixedStorage::new(PREFIX_PRELOAD, storage);
+        let token_data = load_preload(&preload_store, index)?;
+
+        let name = Some("Secret Snail #".to_string() + &token_data.id.to_string());
+        let media = Some(vec![MediaFile {
+            file_type: Some("image".to_string()),
+            extension: Some("gif".to_string()),
+            authentication: Some(Authentication {
+                key: Some("".to_string()),
+                user: Some("".to_string()),
+            }),
+            url: token_data.img_url.clone(),
+        }]);
+        let race_traits = vec![
+            Trait {
+                display_type: None,
+                trait_type: Some(WINS_TRAIT.to_string()),
+                value: 0.to_string(),
+                max_value: None,
+            },
+            Trait {
+                display_type: None,
+                trait_type: Some(LOSES_TRAIT.to_string()),
+                value: 0.to_string(),
+                max_value: None,
+            },
+        ];
+        let category = Trait {
+            display_type: None,
+            trait_type: Some("Category".to_string()),
+            value: "Stephen Hawking".to_string(),
+            max_value: None,
+        };
+
+        // sealed private metadata replaces the public one when the owner reveals the token, so
+        // it has the image and the public metadata only has a placeholder until then.  Wins and
+        // Loses stay in the public metadata only, where the race updates them and the reveal
+        // keeps them
+        let public_media = if sealed_metadata { None } else { media.clone() };
+
+        mints.push(Mint {
+            token_id: Some(token_data.id.clone()),
+            owner: Some(owner.clone()),
+            public_metadata: Some(Metadata {
+                extension: Some(Extension {
+                    image: None,
+                    image_data: None,
+                    external_url: None,
+                    description: None,
+                    name: name.clone(),
+                    attributes: Some(race_traits),
                     background_color: None,
                     animation_url: None,
                     youtube_url: None,
-                    media: Some(vec![MediaFile {
-                        file_type: Some("image".to_string()),
-                        extension: Some("gif".to_string()),
-                        authentication: Some(Authentication {
-                            key: Some("".to_string()),
-                            user: Some("".to_string()),
-                        }),
-                        url: token_data.img_url.clone(),
-                    }]),
+                    media: public_media,
                     protected_attributes: None,
                     token_subtype: None,
                 }),
@@ -426,25 +2260,12 @@ pub fn mint_nfts<S: Storage, A: Api, Q: Querier>(
                     image_data: None,
                     external_url: None,
                     description: None,
-                    name: Some("Secret Snail #".to_string() + &token_data.id.to_string()),
-                    attributes: Some(vec![Trait {
-                        display_type: None,
-                        trait_type: Some("Category".to_string()),
-                        value: "Stephen Hawking".to_string(),
-                        max_value: None,
-                    }]),
+                    name,
+                    attributes: Some(vec![category]),
                     background_color: None,
                     animation_url: None,
                     youtube_url: None,
-                    media: Some(vec![MediaFile {
-                        file_type: Some("image".to_string()),
-                        extension: Some("gif".to_string()),
-                        authentication: Some(Authentication {
-                            key: Some("".to_string()),
-                            user: Some("".to_string()),
-                        }),
-                        url: token_data.img_url.clone(),
-                    }]),
+                    media,
                     protected_attributes: None,
                     token_subtype: None,
                 }),
@@ -461,28 +2282,196 @@ pub fn mint_nfts<S: Storage, A: Api, Q: Querier>(
         });
     }
 
-    let mints_msg = NftsHandleMsg::BatchMintNft {
+    save(storage, COUNT_KEY, &count)?;
+
+    Ok(mints)
+}
+
+/// Number of tokens left in the pool, including the ones reserved by pending mints
+fn pool_count<S: ReadonlyStorage>(storage: &S) -> StdResult<u32> {
+    Ok(may_load(storage, COUNT_KEY)?.unwrap_or(0))
+}
+
+/// Number of tokens reserved by commit-reveal mints that were not revealed yet
+fn reserved_count<S: ReadonlyStorage>(storage: &S) -> StdResult<u32> {
+    Ok(may_load(storage, RESERVED_KEY)?.unwrap_or(0))
+}
+
+/// Load index of the token in pool `slot`, slots that were never swapped hold their own index
+fn pool_slot<S: ReadonlyStorage>(pool_store: &S, slot: u32) -> StdResult<u32> {
+    Ok(may_load(pool_store, &slot.to_le_bytes())?.unwrap_or(slot))
+}
+
+/// Loaded token at `index`
+fn load_preload<S: ReadonlyStorage>(preload_store: &S, index: u32) -> StdResult<PreLoad> {
+    load(preload_store, &index.to_le_bytes())
+}
+
+fn batch_mint_msg(nft_contract: &SecretContract, mints: Vec<Mint>) -> StdResult<CosmosMsg> {
+    NftsHandleMsg::BatchMintNft {
         mints,
         padding: None,
-    };
-
-    let mints_cosmos_msg = mints_msg.to_cosmos_msg(
-        nft_contract.token_code_hash,
-        nft_contract.contract_addr,
+    }
+    .to_cosmos_msg(
+        nft_contract.token_code_hash.clone(),
+        nft_contract.contract_addr.clone(),
         None,
-    )?;
+    )
+}
+
+/// Checks that the revenue split has at least one recipient and that the percentages add up to
+/// exactly 100.0000%
+pub fn validate_revenue_split(revenue_split: &[RevenueSplit]) -> StdResult<()> {
+    if revenue_split.is_empty() {
+        return Err(StdError::generic_err(
+            "Revenue split needs at least one recipient",
+        ));
+    }
 
-    let mut messages = vec![mints_cosmos_msg];
+    let total: u64 = revenue_split.iter().map(|r| r.percentage as u64).sum();
 
-    // TODO: mint revenue
+    if total != REVENUE_SPLIT_TOTAL as u64 {
+        return Err(StdError::generic_err(format!(
+            "Revenue split percentages must add up to {} (100%), got {}",
+            REVENUE_SPLIT_TOTAL, total
+        )));
+    }
 
-    return Ok(HandleResponse {
-        messages,
-        log: vec![],
-        data: Some(to_binary(&HandleAnswer::MintNfts {
-            status: ResponseStatus::Success,
-        })?),
-    });
+    Ok(())
+}
+
+/// Returns the share of `amount` that belongs to each recipient of the revenue split, in the
+/// same order as the split.  Shares are rounded down and the dust left by the rounding goes to
+/// the first recipient, so the shares always add up to `amount`
+pub fn revenue_split_amounts(revenue_split: &[RevenueSplit], amount: Uint128) -> Vec<Uint128> {
+    let total = REVENUE_SPLIT_TOTAL as u128;
+    let amount = amount.u128();
+
+    // split the multiplication so amount * percentage can never overflow
+    let mut shares: Vec<u128> = revenue_split
+        .iter()
+        .map(|r| {
+            let percentage = r.percentage as u128;
+            amount / total * percentage + amount % total * percentage / total
+        })
+        .collect();
+
+    let distributed: u128 = shares.iter().sum();
+    if let Some(first) = shares.first_mut() {
+        *first += amount - distributed;
+    }
+
+    shares.into_iter().map(Uint128).collect()
+}
+
+/// The funds a mint is paid with
+pub enum Payment {
+    Snip20 {
+        token_contract: SecretContract,
+        amount: Uint128,
+        /// price of one mint in this token, the mint price or the phase price apply if None
+        price: Option<Uint128>,
+    },
+    Native {
+        denom: String,
+        amount: Uint128,
+    },
+}
+
+impl Payment {
+    pub fn amount(&self) -> Uint128 {
+        match self {
+            Payment::Snip20 { amount, .. } | Payment::Native { amount, .. } => *amount,
+        }
+    }
+
+    pub fn price(&self) -> Option<Uint128> {
+        match self {
+            Payment::Snip20 { price, .. } => *price,
+            Payment::Native { .. } => None,
+        }
+    }
+
+    /// key of the funds in storage, the token contract address or the native denom
+    fn funds_key(&self) -> Vec<u8> {
+        match self {
+            Payment::Snip20 { token_contract, .. } => {
+                token_contract.contract_addr.0.as_bytes().to_vec()
+            }
+            Payment::Native { denom, .. } => denom.as_bytes().to_vec(),
+        }
+    }
+
+    /// name of the funds used in error messages
+    fn name(&self) -> String {
+        match self {
+            Payment::Snip20 { .. } => "snip20 tokens".to_string(),
+            Payment::Native { denom, .. } => denom.clone(),
+        }
+    }
+
+    /// Returns the message that sends `amount` of the same funds from the contract to
+    /// `recipient`
+    fn send_msg(
+        &self,
+        contract_address: &HumanAddr,
+        recipient: &HumanAddr,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        match self {
+            Payment::Snip20 { token_contract, .. } => transfer_msg(
+                recipient.clone(),
+                amount,
+                None,
+                None,
+                BLOCK_SIZE,
+                token_contract.token_code_hash.clone(),
+                token_contract.contract_addr.clone(),
+            ),
+            Payment::Native { denom, .. } => Ok(CosmosMsg::Bank(BankMsg::Send {
+                from_address: contract_address.clone(),
+                to_address: recipient.clone(),
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount,
+                }],
+            })),
+        }
+    }
+}
+
+/// Returns the messages that pay every revenue split recipient its share of the mint revenue,
+/// in the funds the mint was paid with.  Recipients whose share rounds down to zero are skipped
+pub fn revenue_split_msgs(
+    revenue_split: &[RevenueSplit],
+    payment: &Payment,
+    revenue: Uint128,
+    contract_address: &HumanAddr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
+
+    for (split, share) in revenue_split
+        .iter()
+        .zip(revenue_split_amounts(revenue_split, revenue))
+    {
+        if share.is_zero() {
+            continue;
+        }
+
+        messages.push(payment.send_msg(contract_address, &split.address, share)?);
+    }
+
+    Ok(messages)
+}
+
+/// Returns the entropy of the `index`th token of a pending mint.  It does not depend on who
+/// reveals the mint or in which block, so reverting a reveal and trying again gives the same
+/// tokens unless other mints were committed in between
+fn reveal_entropy(seed: &[u8], pool: &[u8], mint_entropy: &[u8], index: u16) -> [u8; 32] {
+    let rng_entropy = [pool, mint_entropy, &index.to_le_bytes()].concat();
+    let mut rng = Prng::new(seed, &rng_entropy);
+
+    rng.rand_bytes()
 }
 
 pub fn new_entropy(env: &Env, seed: &[u8], entropy: &[u8], index: &[u8]) -> [u8; 32] {
@@ -506,34 +2495,347 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Info {} => query_info(deps),
+        QueryMsg::MintStatus {} => query_mint_status(deps),
+        QueryMsg::Provenance {} => query_provenance(deps),
+        QueryMsg::ProvenanceProof { page, page_size } => {
+            query_provenance_proof(deps, page, page_size)
+        }
+        QueryMsg::PendingMints {} => query_pending_mints(deps),
+        QueryMsg::Roles {} => query_roles(deps),
+        QueryMsg::ContractStatus {} => to_binary(&QueryAnswer::ContractStatus {
+            status: may_load(&deps.storage, CONTRACT_STATUS_KEY)?.unwrap_or_default(),
+        }),
+        QueryMsg::MintPhases { current_time } => query_mint_phases(deps, current_time),
+        QueryMsg::MintPrice { current_time } => query_mint_price(deps, current_time),
+        QueryMsg::WithPermit { permit, query } => permit_queries(deps, permit, query),
+    }
+}
+
+/// Returns StdResult<Binary> from validating a permit and then using its creator's address
+/// when performing the specified query
+pub fn permit_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: Permit,
+    query: QueryWithPermit,
+) -> StdResult<Binary> {
+    let my_address: HumanAddr = load(&deps.storage, MY_ADDRESS_KEY)?;
+    let querier = validate(deps, PREFIX_REVOKED_PERMITS, &permit, my_address)?;
+    if !permit.check_permission(&Permission::Owner) {
+        return Err(StdError::generic_err(format!(
+            "Owner permission is required for minter queries, got permissions {:?}",
+            permit.params.permissions
+        )));
+    }
+
+    match query {
+        QueryWithPermit::WhitelistAllowance { whitelist_proof } => {
+            query_whitelist_allowance(deps, &querier, whitelist_proof)
+        }
+        QueryWithPermit::WalletMints {} => query_wallet_mints(deps, &querier),
+        QueryWithPermit::Whitelist { page, page_size } => {
+            query_whitelist(deps, &querier, page, page_size)
+        }
+        QueryWithPermit::Balances {} => query_balances(deps, &querier),
+    }
+}
+
+/// Returns the minter's balances of the SNIP-20 it has a viewing key for and of the native coin
+pub fn query_balances<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    querier: &HumanAddr,
+) -> StdResult<Binary> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+    check_role(&deps.storage, &config, querier, Role::Treasurer)?;
+
+    let my_address: HumanAddr = load(&deps.storage, MY_ADDRESS_KEY)?;
+    let viewing_keys: Vec<(SecretContract, String)> =
+        may_load(&deps.storage, TOKEN_VIEWING_KEYS_KEY)?.unwrap_or_default();
+    let tokens = viewing_keys
+        .into_iter()
+        .map(|(token, key)| {
+            let balance = balance_query(
+                &deps.querier,
+                my_address.clone(),
+                key,
+                BLOCK_SIZE,
+                token.token_code_hash,
+                token.contract_addr.clone(),
+            )?;
+            Ok(TokenBalance {
+                contract_addr: token.contract_addr,
+                amount: balance.amount,
+            })
+        })
+        .collect::<StdResult<Vec<TokenBalance>>>()?;
+    let native = config
+        .native_denom
+        .map(|denom| deps.querier.query_balance(&my_address, &denom))
+        .transpose()?;
+
+    to_binary(&QueryAnswer::Balances { tokens, native })
+}
+
+/// Returns how many tokens `address` minted and how many more it can mint
+pub fn query_wallet_mints<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> StdResult<Binary> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+    let wallet_store = ReadonlyPrefixedStorage::new(PREFIX_WALLET_MINTED, &deps.storage);
+    let minted: u16 = may_load(&wallet_store, address.0.as_bytes())?.unwrap_or(0);
+
+    to_binary(&QueryAnswer::WalletMints {
+        minted,
+        remaining: config
+            .max_mint_per_wallet
+            .map(|max_mint_per_wallet| max_mint_per_wallet.saturating_sub(minted)),
+    })
+}
+
+/// Returns how many tokens `address` can still mint through the whitelist, from its merkle
+/// whitelist leaf if a proof is given
+pub fn query_whitelist_allowance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    whitelist_proof: Option<WhitelistProof>,
+) -> StdResult<Binary> {
+    let allowance: Option<u16> = if let Some(whitelist_proof) = whitelist_proof {
+        let leaf = verify_whitelist_proof(&deps.storage, address, &whitelist_proof)?;
+        let leaves_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST_LEAVES, &deps.storage);
+        Some(may_load(&leaves_store, &leaf)?.unwrap_or(whitelist_proof.allowance))
+    } else {
+        let white_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST, &deps.storage);
+        may_load(&white_store, address.0.as_bytes())?
+    };
+
+    to_binary(&QueryAnswer::WhitelistAllowance {
+        whitelisted: allowance.is_some(),
+        remaining: allowance.unwrap_or(0),
+    })
+}
+
+/// Returns a page of the stored whitelist, only the admin can list it
+pub fn query_whitelist<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    querier: &HumanAddr,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> StdResult<Binary> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+    check_role(&deps.storage, &config, querier, Role::MintOperator)?;
+
+    // pages cover the slots of the whitelist index, the slots of removed addresses are skipped
+    let slots: u32 = may_load(&deps.storage, WHITELIST_SLOTS_KEY)?.unwrap_or(0);
+    let page_size = page_size.unwrap_or(30);
+    let start = (page.unwrap_or(0) as u64) * (page_size as u64);
+    let end = (start + page_size as u64).min(slots as u64);
+
+    let index_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST_INDEX, &deps.storage);
+    let white_store = ReadonlyPrefixedStorage::new(PREFIX_WHITELIST, &deps.storage);
+    let mut entries = vec![];
+    for slot in start..end {
+        let address: HumanAddr = load(&index_store, &(slot as u32).to_be_bytes())?;
+        if let Some(remaining) = may_load(&white_store, address.0.as_bytes())? {
+            entries.push(WhitelistEntry { address, remaining });
+        }
     }
+
+    to_binary(&QueryAnswer::Whitelist {
+        entries,
+        total: may_load(&deps.storage, WHITELIST_TOTAL_KEY)?.unwrap_or(0),
+    })
 }
 
 fn query_info<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<Binary> {
     let config_store = TypedStore::attach(&deps.storage);
     let config: Config = config_store.load(CONFIG_KEY)?;
 
-    let id: u16 = load(&deps.storage, COUNT_KEY)?;
-
-    let nft_contract = config.nft_contract.unwrap().clone();
-
-    let nft_current_count_response = secret_toolkit::snip721::num_tokens_query(
-        &deps.querier,
-        None,
-        BLOCK_SIZE,
-        nft_contract.token_code_hash.clone(),
-        nft_contract.contract_addr.clone(),
-    )?;
+    let id = pool_count(&deps.storage)?;
+    let reserved = reserved_count(&deps.storage)?;
 
     to_binary(&QueryAnswer::Info {
         admin: config.admin,
+        pending_admin: may_load(&deps.storage, PENDING_ADMIN_KEY)?,
         token_contract: config.token_contract,
-        nft_contract,
+        nft_contract: config.nft_contract,
         mint_price: config.mint_price,
         max_mint_per_tx: config.max_mint_per_tx,
         whitelist_mint_enabled: config.whitelist_mint_enabled,
         standard_mint_enabled: config.standard_mint_enabled,
-        mint_current_count: nft_current_count_response.count,
-        mint_current_left: id,
+        mint_current_count: may_load(&deps.storage, MINTED_COUNT_KEY)?.unwrap_or(0),
+        mint_current_left: id.saturating_sub(reserved),
+        commit_reveal_enabled: config.commit_reveal_enabled,
+        native_denom: config.native_denom,
+        payment_tokens: may_load(&deps.storage, PAYMENT_TOKENS_KEY)?.unwrap_or_default(),
+        max_mint_per_wallet: config.max_mint_per_wallet,
+        sealed_metadata: config.sealed_metadata,
+    })
+}
+
+/// Supply counters kept by the minter itself, so they don't depend on the NFT contract being
+/// set or on its token supply being public
+fn query_mint_status<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<Binary> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    let count = pool_count(&deps.storage)?;
+    let reserved = reserved_count(&deps.storage)?;
+    let minted: u32 = may_load(&deps.storage, MINTED_COUNT_KEY)?.unwrap_or(0);
+
+    let phases: Vec<StoredMintPhase> =
+        may_load(&deps.storage, MINT_PHASES_KEY)?.unwrap_or_default();
+    let total_store = ReadonlyPrefixedStorage::new(PREFIX_PHASE_TOTAL_MINTED, &deps.storage);
+    let phases = phases
+        .iter()
+        .map(|phase| {
+            Ok(PhaseMintStatus {
+                name: phase.name.clone(),
+                start_time: phase.start_time,
+                end_time: phase.end_time,
+                minted: may_load(&total_store, &phase.id.to_le_bytes())?.unwrap_or(0),
+            })
+        })
+        .collect::<StdResult<Vec<PhaseMintStatus>>>()?;
+
+    to_binary(&QueryAnswer::MintStatus {
+        nft_contract: config.nft_contract,
+        total_loaded: minted.saturating_add(count),
+        minted,
+        pending: reserved,
+        remaining: count.saturating_sub(reserved),
+        phases,
+    })
+}
+
+fn query_provenance<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<Binary> {
+    let count = pool_count(&deps.storage)?;
+    let minted: u32 = may_load(&deps.storage, MINTED_COUNT_KEY)?.unwrap_or(0);
+    let hash: Option<Vec<u8>> = may_load(&deps.storage, PRELOAD_HASH_KEY)?;
+
+    to_binary(&QueryAnswer::Provenance {
+        hash: hash.map(Binary),
+        total_loaded: minted.saturating_add(count),
+        locked: may_load(&deps.storage, METADATA_LOCKED_KEY)?.unwrap_or(false),
+    })
+}
+
+fn query_provenance_proof<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> StdResult<Binary> {
+    let count = pool_count(&deps.storage)?;
+    let minted: u32 = may_load(&deps.storage, MINTED_COUNT_KEY)?.unwrap_or(0);
+    let locked: bool = may_load(&deps.storage, METADATA_LOCKED_KEY)?.unwrap_or(false);
+
+    // before the sell out the proof would tell which tokens are left to mint
+    if count > 0 || minted == 0 || !locked {
+        return Err(StdError::generic_err(
+            "The provenance proof is only available once every token is minted",
+        ));
+    }
+
+    let page_size = page_size.unwrap_or(30);
+    let skip = page
+        .unwrap_or(0)
+        .checked_mul(page_size)
+        .ok_or_else(|| StdError::generic_err("Page is out of range"))?;
+    let preload_store = ReadonlyPrefixedStorage::new(PREFIX_PRELOAD, &deps.storage);
+    let tokens = (1..=minted)
+        .skip(skip as usize)
+        .take(page_size as usize)
+        .map(|index| load_preload(&preload_store, index))
+        .collect::<StdResult<Vec<PreLoad>>>()?;
+
+    to_binary(&QueryAnswer::ProvenanceProof {
+        tokens,
+        total: minted,
+    })
+}
+
+fn query_roles<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<Binary> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    let roles = Role::ALL
+        .iter()
+        .map(|role| {
+            Ok(RoleHolders {
+                role: *role,
+                addresses: role_holders(&deps.storage, &config, *role)?,
+            })
+        })
+        .collect::<StdResult<Vec<RoleHolders>>>()?;
+
+    to_binary(&QueryAnswer::Roles { roles })
+}
+
+fn query_pending_mints<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<Binary> {
+    let pending_ids: Vec<u32> = may_load(&deps.storage, PENDING_MINT_IDS_KEY)?.unwrap_or_default();
+    let pending_store = ReadonlyPrefixedStorage::new(PREFIX_PENDING_MINTS, &deps.storage);
+
+    let pending_mints = pending_ids
+        .into_iter()
+        .map(|mint_id| {
+            let pending: PendingMint = load(&pending_store, &mint_id.to_le_bytes())?;
+            Ok(PendingMintInfo {
+                mint_id,
+                count: pending.count,
+                commit_height: pending.commit_height,
+            })
+        })
+        .collect::<StdResult<Vec<PendingMintInfo>>>()?;
+
+    to_binary(&QueryAnswer::PendingMints { pending_mints })
+}
+
+fn query_mint_price<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    current_time: u64,
+) -> StdResult<Binary> {
+    let config: Config = TypedStore::attach(&deps.storage).load(CONFIG_KEY)?;
+    let auction: Option<DutchAuction> = may_load(&deps.storage, DUTCH_AUCTION_KEY)?;
+    let phases: Vec<StoredMintPhase> =
+        may_load(&deps.storage, MINT_PHASES_KEY)?.unwrap_or_default();
+
+    let price = match &auction {
+        Some(auction) => auction.price_at(current_time),
+        None if phases.is_empty() => config.mint_price,
+        None => {
+            active_phase(&phases, current_time)
+                .ok_or_else(|| StdError::generic_err("No mint phase is active"))?
+                .price
+        }
+    };
+
+    to_binary(&QueryAnswer::MintPrice { price, auction })
+}
+
+fn query_mint_phases<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    current_time: u64,
+) -> StdResult<Binary> {
+    let phases: Vec<StoredMintPhase> =
+        may_load(&deps.storage, MINT_PHASES_KEY)?.unwrap_or_default();
+
+    to_binary(&QueryAnswer::MintPhases {
+        current_phase: active_phase(&phases, current_time).map(mint_phase_info),
+        upcoming_phases: phases
+            .iter()
+            .filter(|p| p.start_time > current_time)
+            .map(mint_phase_info)
+            .collect(),
     })
 }
+
+fn mint_phase_info(phase: &StoredMintPhase) -> MintPhaseInfo {
+    MintPhaseInfo {
+        name: phase.name.clone(),
+        start_time: phase.start_time,
+        end_time: phase.end_time,
+        price: phase.price,
+        max_per_wallet: phase.max_per_wallet,
+        max_per_tx: phase.max_per_tx,
+        restricted: phase.restricted,
+    }
+}
@@ -1,6 +1,8 @@
 pub mod contract;
 pub mod msg;
 pub mod state;
+mod unittest_handles;
+mod unittest_mint_flow;
 
 #[cfg(all(target_arch = "wasm32", not(feature = "library")))]
 cosmwasm_std::create_entry_points!(contract);
@@ -1,9 +1,10 @@
 use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 use schemars::JsonSchema;
-use secret_toolkit::utils::{HandleCallback, InitCallback};
+use secret_toolkit::permit::Permit;
+use secret_toolkit::utils::{HandleCallback, InitCallback, Query};
 use serde::{Deserialize, Serialize};
 
-use crate::state::{SecretContract, BLOCK_SIZE};
+use crate::state::{ContractStatus, DutchAuction, PaymentToken, Role, SecretContract, BLOCK_SIZE};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
@@ -12,8 +13,34 @@ pub struct InitMsg {
     pub entropy: String,
     pub mint_price: Uint128,
     pub max_mint_per_tx: u16,
-    pub whitelist: Vec<HumanAddr>,
+    /// whitelisted addresses and how many tokens each can mint through the whitelist
+    pub whitelist: Vec<(HumanAddr, u16)>,
+    /// merkle root of a whitelist too big to store, see WhitelistProof
+    pub whitelist_merkle_root: Option<Binary>,
+    /// native coin denom (e.g. uscrt) that can pay for mints with the MintNfts handle
+    pub native_denom: Option<String>,
+    /// how many tokens an address can mint in total, no limit if omitted
+    pub max_mint_per_wallet: Option<u16>,
     pub revenue_split: Vec<RevenueSplit>,
+    /// instantiates the NFT contract with the minter's admin as its admin and the minter as a
+    /// minter.  The new contract registers itself back with its post init callback, instead of
+    /// using AddNftContract
+    pub nft_contract_init: Option<NftContractInit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftContractInit {
+    /// code id of the uploaded secret-snails-nft contract
+    pub code_id: u64,
+    pub code_hash: String,
+    /// label of the new contract, has to be unique
+    pub label: String,
+    pub name: String,
+    pub symbol: String,
+    pub royalty_info: Option<RoyaltyInfo>,
+    pub config: Option<InitConfig>,
+    /// addresses allowed to view the hidden attributes of the tokens, like the race contract
+    pub hidden_attribute_viewers: Option<Vec<HumanAddr>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -31,30 +58,186 @@ pub enum HandleMsg {
         amount: Uint128,
         msg: Binary,
     },
+    /// Mints paying with the native coin sent with the message, the SNIP-20 mints are sent
+    /// through Receive
+    MintNfts {
+        count: u16,
+        /// optional entropy mixed into the random pick of the tokens
+        entropy: Option<String>,
+        /// proves the sender is on the merkle whitelist
+        whitelist_proof: Option<WhitelistProof>,
+        /// mints as many of the tokens as the supply, the limits and the payment allow and
+        /// refunds the rest of the payment, instead of failing
+        partial_fill: Option<bool>,
+    },
     UpdateMint {
         whitelist_mint_enabled: bool,
         standard_mint_enabled: bool,
         mint_price: Option<Uint128>,
         max_mint_per_tx: Option<u16>,
+        /// when enabled mints only reserve their tokens, which are picked later by RevealMints
+        commit_reveal_enabled: Option<bool>,
     },
     UpdateChangeMetadataPermitedAdresses {
         change_metadata_permited_addresses: Vec<HumanAddr>,
     },
-    ChangeAdmin {
-        admin: HumanAddr,
+    /// Sets how many tokens each address can still mint through the whitelist
+    AddToWhitelist {
+        entries: Vec<(HumanAddr, u16)>,
+    },
+    RemoveFromWhitelist {
+        addresses: Vec<HumanAddr>,
+    },
+    /// disallow the use of a permit
+    RevokePermit {
+        /// name of the permit that is no longer valid
+        permit_name: String,
+    },
+    /// Sets or removes the merkle root of the whitelist
+    SetWhitelistMerkleRoot {
+        root: Option<Binary>,
+    },
+    /// Accepts another SNIP-20 to pay for mints at its own price, or updates its price
+    AddPaymentToken {
+        token: SecretContract,
+        price: Uint128,
+    },
+    RemovePaymentToken {
+        contract_addr: HumanAddr,
+    },
+    /// Proposes a new admin, who becomes admin once it sends AcceptAdmin
+    ProposeAdmin {
+        address: HumanAddr,
+    },
+    /// Sent by the proposed admin to take over the admin rights
+    AcceptAdmin {},
+    /// Withdraws the pending admin proposal
+    CancelAdminProposal {},
+    /// Pauses or resumes minting, metadata loading and game updates, or stops every handle
+    SetContractStatus {
+        status: ContractStatus,
+    },
+    /// Lets `address` perform the actions of `role` without the admin key
+    GrantRole {
+        role: Role,
+        address: HumanAddr,
+    },
+    /// Takes `role` back from `address`.  Revoking the game operator role also removes the
+    /// address from the ones set with UpdateChangeMetadataPermitedAdresses
+    RevokeRole {
+        role: Role,
+        address: HumanAddr,
     },
     AddNftContract {
         contract: SecretContract,
+        /// true if the NFT contract has sealed metadata enabled, defaults to false
+        sealed_metadata: Option<bool>,
+    },
+    /// Post init callback of the NFT contract instantiated with the minter
+    RegisterNftContract {},
+    /// Replaces the addresses the mint revenue is split between, only while the mint is stopped
+    /// or paused by the contract status
+    UpdateRevenueSplit {
+        revenue_split: Vec<RevenueSplit>,
+    },
+    /// Sends SNIP-20 tokens held by the minter, like dust left by the revenue split.  Tokens
+    /// kept for unclaimed dutch auction rebates can't be withdrawn, which needs the viewing key
+    /// set with SetTokenViewingKey to check the balance
+    WithdrawToken {
+        token: SecretContract,
+        amount: Uint128,
+        recipient: HumanAddr,
+    },
+    /// Sends native coins held by the minter, except the ones kept for unclaimed dutch auction
+    /// rebates
+    WithdrawNative {
+        denom: String,
+        amount: Uint128,
+        recipient: HumanAddr,
+    },
+    /// Sets the viewing key of the minter on a SNIP-20, so the treasurers can see its balance
+    /// with the Balances query
+    SetTokenViewingKey {
+        token: SecretContract,
+        key: String,
     },
     LoadMetadata {
         new_data: Vec<PreLoad>,
     },
+    /// Adds the race results to the public Wins/Loses traits of a snail.  Only callable by the
+    /// change_metadata_permited_addresses
+    UpdateSnailStats {
+        token_id: String,
+        wins_delta: u32,
+        loses_delta: u32,
+    },
+    /// Replaces the mint schedule.  While phases are set the phase active at the block time
+    /// decides who can mint and at which price, and the UpdateMint flags, price and max per tx
+    /// are ignored.  An empty list goes back to UpdateMint.  A phase keeps what was minted in it
+    /// as long as it keeps its name
+    SetMintPhases {
+        phases: Vec<MintPhase>,
+    },
+    /// Sets or removes the dutch auction.  While it is set the auction price replaces the mint
+    /// price and the phase prices, other payment tokens keep their own price.  An auction with
+    /// rebates can't be changed until every purchase made in it was claimed
+    SetDutchAuction {
+        auction: Option<DutchAuction>,
+    },
+    /// Sends back what the sender paid over the clearing price of the dutch auction, once the
+    /// auction is over.  The clearing price is the price of the last token if the collection
+    /// sold out, or the floor price
+    ClaimAuctionRebate {},
+    /// Sets how many tokens an address can mint in total, across all mints and phases.  Tokens
+    /// minted before the cap was set count against it
+    SetMaxMintPerWallet {
+        max_mint_per_wallet: Option<u16>,
+    },
+    /// Picks and mints the tokens reserved by commit-reveal mints.  Callable by anyone once the
+    /// block the mint was committed in is over
+    RevealMints {
+        mint_ids: Vec<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintPhase {
+    pub name: String,
+    /// block time in seconds the phase starts at
+    pub start_time: u64,
+    /// block time in seconds the phase ends at, the phase never ends if omitted
+    pub end_time: Option<u64>,
+    pub price: Uint128,
+    /// how many tokens an address can mint during the whole phase, no limit if omitted
+    pub max_per_wallet: Option<u16>,
+    pub max_per_tx: u16,
+    /// the only addresses allowed to mint during the phase, anybody can mint if omitted
+    pub allowed_addresses: Option<Vec<HumanAddr>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleReceiveMsg {
-    MintNfts { count: u16 },
+    MintNfts {
+        count: u16,
+        /// optional entropy mixed into the random pick of the tokens
+        entropy: Option<String>,
+        /// proves the sender is on the merkle whitelist
+        whitelist_proof: Option<WhitelistProof>,
+        /// mints as many of the tokens as the supply, the limits and the payment allow and
+        /// refunds the rest of the payment, instead of failing
+        partial_fill: Option<bool>,
+    },
+}
+
+/// Proof that an address is in the merkle whitelist.  The leaves of the tree are the sha256 of
+/// the address followed by its allowance as a big endian u16, and each parent node is the
+/// sha256 of its two children sorted bytewise
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistProof {
+    pub allowance: u16,
+    /// the sibling hashes from the leaf up to the root
+    pub proof: Vec<Binary>,
 }
 
 /// Preloaded data storage for the tokens during init
@@ -71,10 +254,31 @@ pub struct PreLoad {
 #[serde(rename_all = "snake_case")]
 pub enum HandleAnswer {
     AddNftContract { status: ResponseStatus },
+    RegisterNftContract { status: ResponseStatus },
+    UpdateRevenueSplit { status: ResponseStatus },
+    Withdraw { status: ResponseStatus },
+    SetTokenViewingKey { status: ResponseStatus },
     MintNfts { status: ResponseStatus },
-    ChangeAdmin { status: ResponseStatus },
+    ProposeAdmin { status: ResponseStatus },
+    AcceptAdmin { status: ResponseStatus },
+    CancelAdminProposal { status: ResponseStatus },
+    SetContractStatus { status: ResponseStatus },
+    GrantRole { status: ResponseStatus },
+    RevokeRole { status: ResponseStatus },
     UpdateChangeMetadataPermitedAdresses { status: ResponseStatus },
     UpdateMint { status: ResponseStatus },
+    AddToWhitelist { status: ResponseStatus },
+    RemoveFromWhitelist { status: ResponseStatus },
+    SetWhitelistMerkleRoot { status: ResponseStatus },
+    RevokePermit { status: ResponseStatus },
+    AddPaymentToken { status: ResponseStatus },
+    RemovePaymentToken { status: ResponseStatus },
+    UpdateSnailStats { status: ResponseStatus },
+    SetMintPhases { status: ResponseStatus },
+    SetMaxMintPerWallet { status: ResponseStatus },
+    SetDutchAuction { status: ResponseStatus },
+    ClaimAuctionRebate { status: ResponseStatus },
+    RevealMints { status: ResponseStatus },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -88,6 +292,64 @@ pub enum ResponseStatus {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Info {},
+    /// loaded, minted and remaining tokens, counted by the minter
+    MintStatus {},
+    /// commitment to the loaded tokens.  Starting from an empty hash, every loaded token in
+    /// order updates it to sha256(hash ++ sha256(token)), where token is the PreLoad as JSON
+    Provenance {},
+    /// the loaded tokens in their load order, to recompute the provenance hash and check the
+    /// revealed tokens against it.  Only available once every token is minted
+    ProvenanceProof {
+        /// optional page to display, defaults to the first one
+        page: Option<u32>,
+        /// optional max number of tokens to display, defaults to 30
+        page_size: Option<u32>,
+    },
+    /// commit-reveal mints waiting to be revealed
+    PendingMints {},
+    /// the addresses holding each role
+    Roles {},
+    /// which parts of the minter are stopped
+    ContractStatus {},
+    /// the phase active at `current_time` and the ones after it.  Queries can't read the block
+    /// time, so the caller has to provide it
+    MintPhases {
+        current_time: u64,
+    },
+    /// the price of one mint paid with the token contract or the native coin at `current_time`
+    MintPrice {
+        current_time: u64,
+    },
+    /// perform queries by passing permits instead of viewing keys
+    WithPermit {
+        /// permit used to verify querier identity
+        permit: Permit,
+        /// query to perform
+        query: QueryWithPermit,
+    },
+}
+
+/// queries using permits instead of viewing keys
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWithPermit {
+    /// how many tokens the permit signer can still mint through the whitelist.  The proof is
+    /// only needed to check a merkle whitelist allowance
+    WhitelistAllowance {
+        whitelist_proof: Option<WhitelistProof>,
+    },
+    /// how many tokens the permit signer minted and how many more it can mint
+    WalletMints {},
+    /// lists the stored whitelist, only the admin and the mint operators can see it
+    Whitelist {
+        /// optional page to display, defaults to the first one.  Pages list the addresses in the
+        /// order they were first whitelisted, removed addresses leave their page short
+        page: Option<u32>,
+        /// optional max number of addresses to display, defaults to 30
+        page_size: Option<u32>,
+    },
+    /// the minter's balances, only the admin and the treasurers can see them
+    Balances {},
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -95,15 +357,190 @@ pub enum QueryMsg {
 pub enum QueryAnswer {
     Info {
         admin: HumanAddr,
+        /// address proposed as the new admin, waiting for it to accept
+        pending_admin: Option<HumanAddr>,
         token_contract: SecretContract,
-        nft_contract: SecretContract,
+        nft_contract: Option<SecretContract>,
         mint_price: Uint128,
         whitelist_mint_enabled: bool,
         standard_mint_enabled: bool,
         max_mint_per_tx: u16,
         mint_current_count: u32,
-        mint_current_left: u16,
+        mint_current_left: u32,
+        commit_reveal_enabled: bool,
+        native_denom: Option<String>,
+        /// other SNIP-20 accepted to pay for mints and their prices
+        payment_tokens: Vec<PaymentToken>,
+        max_mint_per_wallet: Option<u16>,
+        sealed_metadata: bool,
+    },
+    MintStatus {
+        nft_contract: Option<SecretContract>,
+        /// tokens loaded since the contract was instantiated
+        total_loaded: u32,
+        minted: u32,
+        /// tokens reserved by commit-reveal mints that are not revealed yet
+        pending: u32,
+        /// tokens that can still be bought
+        remaining: u32,
+        /// tokens bought in each of the current mint phases
+        phases: Vec<PhaseMintStatus>,
+    },
+    Provenance {
+        /// None until tokens are loaded
+        hash: Option<Binary>,
+        total_loaded: u32,
+        /// true once minting was enabled, no more tokens can be loaded
+        locked: bool,
+    },
+    ProvenanceProof {
+        tokens: Vec<PreLoad>,
+        /// number of loaded tokens
+        total: u32,
+    },
+    PendingMints {
+        pending_mints: Vec<PendingMintInfo>,
+    },
+    MintPhases {
+        current_phase: Option<MintPhaseInfo>,
+        upcoming_phases: Vec<MintPhaseInfo>,
+    },
+    MintPrice {
+        price: Uint128,
+        auction: Option<DutchAuction>,
+    },
+    WalletMints {
+        minted: u16,
+        /// how many more tokens the address can mint, no limit if None
+        remaining: Option<u16>,
+    },
+    WhitelistAllowance {
+        whitelisted: bool,
+        remaining: u16,
+    },
+    Whitelist {
+        entries: Vec<WhitelistEntry>,
+        /// number of whitelisted addresses
+        total: u32,
+    },
+    Roles {
+        roles: Vec<RoleHolders>,
+    },
+    ContractStatus {
+        status: ContractStatus,
     },
+    Balances {
+        /// balances of the SNIP-20 with a viewing key set by SetTokenViewingKey
+        tokens: Vec<TokenBalance>,
+        /// balance of the native coin accepted to mint
+        native: Option<Coin>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleHolders {
+    pub role: Role,
+    /// addresses granted the role, the admin holds every role but the game operator one
+    /// without being listed
+    pub addresses: Vec<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenBalance {
+    pub contract_addr: HumanAddr,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistEntry {
+    pub address: HumanAddr,
+    pub remaining: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintPhaseInfo {
+    pub name: String,
+    pub start_time: u64,
+    pub end_time: Option<u64>,
+    pub price: Uint128,
+    pub max_per_wallet: Option<u16>,
+    pub max_per_tx: u16,
+    /// true if only some addresses are allowed to mint during the phase
+    pub restricted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PhaseMintStatus {
+    pub name: String,
+    pub start_time: u64,
+    pub end_time: Option<u64>,
+    pub minted: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingMintInfo {
+    pub mint_id: u32,
+    pub count: u16,
+    pub commit_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, Debug)]
+pub struct NftsInitMsg {
+    /// name of token contract
+    pub name: String,
+    /// token contract symbol
+    pub symbol: String,
+    /// optional admin address, env.message.sender if missing
+    pub admin: Option<HumanAddr>,
+    /// entropy used for prng seed
+    pub entropy: String,
+    /// optional royalty information to use as default when RoyaltyInfo is not provided to a
+    /// minting function
+    pub royalty_info: Option<RoyaltyInfo>,
+    /// optional privacy configuration for the contract
+    pub config: Option<InitConfig>,
+    /// optional callback message to execute after instantiation
+    pub post_init_callback: Option<PostInitCallback>,
+    /// optional list of addresses allowed to view the hidden attributes of the tokens
+    pub hidden_attribute_viewers: Option<Vec<HumanAddr>>,
+    /// optional list of addresses allowed to mint besides the admin
+    pub minters: Option<Vec<HumanAddr>>,
+}
+
+impl InitCallback for NftsInitMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+/// optional privacy configuration of the NFT contract, its defaults are the most private
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InitConfig {
+    /// default: False
+    pub public_token_supply: Option<bool>,
+    /// default: False
+    pub public_owner: Option<bool>,
+    /// default: False
+    pub enable_sealed_metadata: Option<bool>,
+    /// default: False
+    pub unwrapped_metadata_is_private: Option<bool>,
+    /// default: True
+    pub minter_may_update_metadata: Option<bool>,
+    /// default: False
+    pub owner_may_update_metadata: Option<bool>,
+    /// default: False
+    pub enable_burn: Option<bool>,
+}
+
+/// info needed to perform a callback message after instantiation
+#[derive(Serialize, Deserialize, Clone, JsonSchema, Debug)]
+pub struct PostInitCallback {
+    /// the callback message to execute
+    pub msg: Binary,
+    /// address of the contract to execute
+    pub contract_address: HumanAddr,
+    /// code hash of the contract to execute
+    pub code_hash: String,
+    /// list of native Coin to send with the callback message
+    pub send: Vec<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, JsonSchema, Debug)]
@@ -137,12 +574,43 @@ pub enum NftsHandleMsg {
         /// optional message length padding
         padding: Option<String>,
     },
+    /// set the public and/or private metadata
+    SetMetadata {
+        /// id of the token whose metadata should be updated
+        token_id: String,
+        /// the optional new public metadata
+        public_metadata: Option<Metadata>,
+        /// the optional new private metadata
+        private_metadata: Option<Metadata>,
+        /// optional message length padding
+        padding: Option<String>,
+    },
 }
 
 impl HandleCallback for NftsHandleMsg {
     const BLOCK_SIZE: usize = BLOCK_SIZE;
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftsQueryMsg {
+    /// displays the public metadata of a token
+    NftInfo { token_id: String },
+}
+
+impl Query for NftsQueryMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftsQueryAnswer {
+    NftInfo {
+        token_uri: Option<String>,
+        extension: Option<Extension>,
+    },
+}
+
 /// token mint info used when doing a BatchMint
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Mint {
@@ -15,6 +15,108 @@ pub struct SecretContract {
     pub token_code_hash: String,
 }
 
+/// Which parts of the minter are stopped, each one independently of the others
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Default, JsonSchema)]
+pub struct ContractStatus {
+    /// mints are refused, the SNIP-20 sent to mint are refunded
+    pub mint_paused: bool,
+    /// LoadMetadata is refused
+    pub metadata_paused: bool,
+    /// UpdateSnailStats is refused
+    pub game_paused: bool,
+    /// every handle but SetContractStatus is refused, the SNIP-20 received are refunded
+    pub stop_all: bool,
+}
+
+/// What an address is allowed to do on the minter.  The admin holds every role but the game
+/// operator one, token stats only change through the addresses granted it
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// the address in the config, it changes with ProposeAdmin and can't be granted
+    Admin,
+    /// opens and closes the mint and its phases, and sets the prices, limits and whitelist
+    MintOperator,
+    /// loads the tokens to mint
+    MetadataLoader,
+    /// withdraws the funds of the minter and views its balances
+    Treasurer,
+    /// updates the metadata of minted tokens, like the race results
+    GameOperator,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::Admin,
+        Role::MintOperator,
+        Role::MetadataLoader,
+        Role::Treasurer,
+        Role::GameOperator,
+    ];
+
+    /// name of the role, also the storage key of its holders
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::MintOperator => "mint operator",
+            Role::MetadataLoader => "metadata loader",
+            Role::Treasurer => "treasurer",
+            Role::GameOperator => "game operator",
+        }
+    }
+}
+
+/// A SNIP-20 accepted to pay for mints besides the token contract
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PaymentToken {
+    pub contract: SecretContract,
+    /// price of one mint paid in this token, whatever mint phase is running
+    pub price: Uint128,
+}
+
+/// Dutch auction pricing, the price drops from the start price to the floor price in steps
+/// between the start and the end time
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, JsonSchema)]
+pub struct DutchAuction {
+    /// block time in seconds the price starts dropping at
+    pub start_time: u64,
+    /// block time in seconds the price reaches the floor price at
+    pub end_time: u64,
+    pub start_price: Uint128,
+    pub floor_price: Uint128,
+    /// seconds between two price drops
+    pub step_duration: u64,
+    /// if true buyers can claim back what they paid over the clearing price once the auction
+    /// is over
+    pub rebate: bool,
+}
+
+impl DutchAuction {
+    /// Returns the price of one mint at block time `time`
+    pub fn price_at(&self, time: u64) -> Uint128 {
+        if time <= self.start_time {
+            return self.start_price;
+        }
+        if time >= self.end_time {
+            return self.floor_price;
+        }
+
+        let elapsed = time - self.start_time;
+        let elapsed = (elapsed - elapsed % self.step_duration) as u128;
+        let duration = (self.end_time - self.start_time) as u128;
+        let drop = self.start_price.u128() - self.floor_price.u128();
+
+        Uint128(self.start_price.u128() - drop * elapsed / duration)
+    }
+}
+
+/// What an address paid for its dutch auction mints, kept until it claims its rebate
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AuctionPurchase {
+    pub paid: Uint128,
+    pub count: u16,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub struct Config {
     pub admin: HumanAddr,
@@ -26,6 +128,42 @@ pub struct Config {
     pub max_mint_per_tx: u16,
     pub revenue_split: Vec<RevenueSplit>,
     pub change_metadata_permited_addresses: Vec<HumanAddr>,
+    pub commit_reveal_enabled: bool,
+    /// native coin denom accepted by the MintNfts handle, native mints are disabled if None
+    pub native_denom: Option<String>,
+    /// how many tokens an address can mint in total, across all mints and phases
+    pub max_mint_per_wallet: Option<u16>,
+    /// the NFT contract seals the private metadata, so the image is only in it until the owner
+    /// reveals the token
+    pub sealed_metadata: bool,
+}
+
+/// Mint phase as stored by the contract.  The allowed addresses are kept in their own storage
+/// under the allowed list id
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct StoredMintPhase {
+    /// kept while the phase keeps its name, so its wallet counts and minted total carry over
+    /// when the schedule is set again
+    pub id: u32,
+    /// unique among all the schedules ever set, so stale allowed addresses are never read again
+    pub allowed_list: u32,
+    pub name: String,
+    pub start_time: u64,
+    pub end_time: Option<u64>,
+    pub price: Uint128,
+    pub max_per_wallet: Option<u16>,
+    pub max_per_tx: u16,
+    pub restricted: bool,
+}
+
+/// Mint paid for and reserved in commit-reveal mode, waiting to be revealed
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PendingMint {
+    pub owner: HumanAddr,
+    pub count: u16,
+    pub commit_height: u64,
+    /// entropy gathered when the mint was committed, including the one supplied by the minter
+    pub entropy: Vec<u8>,
 }
 
 /// Returns StdResult<()> resulting from saving an item to storage
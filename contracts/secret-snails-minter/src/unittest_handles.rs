@@ -0,0 +1,3357 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{
+        handle, init, preload_hash, query, query_balances, query_wallet_mints, query_whitelist,
+        query_whitelist_allowance, revenue_split_amounts, whitelist_leaf,
+    };
+    use crate::msg::{
+        Extension, HandleMsg, HandleReceiveMsg, HiddenAttribute, InitMsg, Metadata, Mint,
+        MintPhase, MintPhaseInfo, NftContractInit, NftsHandleMsg, NftsInitMsg, NftsQueryAnswer,
+        NftsQueryMsg, PendingMintInfo, PhaseMintStatus, PreLoad, QueryAnswer, QueryMsg,
+        QueryWithPermit, RevenueSplit, TokenBalance, Trait, WhitelistEntry, WhitelistProof,
+    };
+    use crate::state::{save, ContractStatus, DutchAuction, Role, SecretContract, BLOCK_SIZE};
+    use cosmwasm_std::testing::*;
+    use cosmwasm_std::{
+        coins, from_binary, from_slice, log, to_binary, Api, BankMsg, Binary, CosmosMsg, Empty,
+        Env, Extern, HandleResponse, HumanAddr, InitResponse, Querier, QuerierResult, QueryRequest,
+        ReadonlyStorage, StdError, StdResult, Storage, SystemError, Uint128, WasmMsg, WasmQuery,
+    };
+    use secret_toolkit::permit::{Permission, Permit, PermitParams, PermitSignature, PubKey};
+    use secret_toolkit::snip20::{register_receive_msg, set_viewing_key_msg, transfer_msg};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use std::any::Any;
+
+    // Helper functions
+
+    fn token_contract() -> SecretContract {
+        SecretContract {
+            contract_addr: HumanAddr("sscrt".to_string()),
+            token_code_hash: "sscrt_hash".to_string(),
+        }
+    }
+
+    fn nft_contract() -> SecretContract {
+        SecretContract {
+            contract_addr: HumanAddr("snails".to_string()),
+            token_code_hash: "snails_hash".to_string(),
+        }
+    }
+
+    fn split(address: &str, percentage: u32) -> RevenueSplit {
+        RevenueSplit {
+            address: HumanAddr(address.to_string()),
+            percentage,
+        }
+    }
+
+    fn init_helper(
+        revenue_split: Vec<RevenueSplit>,
+    ) -> (
+        StdResult<InitResponse>,
+        Extern<MockStorage, MockApi, MockQuerier>,
+    ) {
+        let mut deps = mock_dependencies(20, &[]);
+        let env = mock_env("instantiator", &[]);
+
+        let init_msg = InitMsg {
+            admin: Some(HumanAddr("admin".to_string())),
+            token_contract: token_contract(),
+            entropy: "We're going to need a bigger boat".to_string(),
+            mint_price: Uint128(1_000_000),
+            max_mint_per_tx: 5,
+            whitelist: vec![],
+            whitelist_merkle_root: None,
+            native_denom: Some("uscrt".to_string()),
+            max_mint_per_wallet: None,
+            revenue_split,
+            nft_contract_init: None,
+        };
+
+        (init(&mut deps, env, init_msg), deps)
+    }
+
+    /// inits the minter, adds the nft contract, loads `token_count` tokens and opens the mint
+    fn mint_ready_helper(
+        revenue_split: Vec<RevenueSplit>,
+        token_count: u16,
+    ) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let (init_result, mut deps) = init_helper(revenue_split);
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+
+        let handle_msg = HandleMsg::AddNftContract {
+            contract: nft_contract(),
+            sealed_metadata: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let new_data = (1..=token_count)
+            .map(|id| PreLoad {
+                id: id.to_string(),
+                img_url: format!("https://snails.io/{}.gif", id),
+                attributes: None,
+                priv_attributes: None,
+                hidden_attributes: None,
+            })
+            .collect();
+        let handle_msg = HandleMsg::LoadMetadata { new_data };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        deps
+    }
+
+    fn receive_msg(from: &str, amount: u128, count: u16) -> HandleMsg {
+        whitelist_receive_msg(from, amount, count, None)
+    }
+
+    fn whitelist_receive_msg(
+        from: &str,
+        amount: u128,
+        count: u16,
+        whitelist_proof: Option<WhitelistProof>,
+    ) -> HandleMsg {
+        mint_receive_msg(from, amount, count, whitelist_proof, None)
+    }
+
+    fn partial_receive_msg(from: &str, amount: u128, count: u16) -> HandleMsg {
+        mint_receive_msg(from, amount, count, None, Some(true))
+    }
+
+    fn mint_receive_msg(
+        from: &str,
+        amount: u128,
+        count: u16,
+        whitelist_proof: Option<WhitelistProof>,
+        partial_fill: Option<bool>,
+    ) -> HandleMsg {
+        HandleMsg::Receive {
+            sender: HumanAddr(from.to_string()),
+            from: HumanAddr(from.to_string()),
+            amount: Uint128(amount),
+            msg: to_binary(&HandleReceiveMsg::MintNfts {
+                count,
+                entropy: None,
+                whitelist_proof,
+                partial_fill,
+            })
+            .unwrap(),
+        }
+    }
+
+    fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+        if a <= b {
+            Sha256::digest(&[a, b].concat()).to_vec()
+        } else {
+            Sha256::digest(&[b, a].concat()).to_vec()
+        }
+    }
+
+    /// builds a merkle whitelist of four entries, returns its root and the proof of each entry
+    fn merkle_whitelist(entries: [(&str, u16); 4]) -> (Binary, Vec<WhitelistProof>) {
+        let leaves: Vec<Vec<u8>> = entries
+            .iter()
+            .map(|(address, allowance)| whitelist_leaf(&HumanAddr(address.to_string()), *allowance))
+            .collect();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        let root = hash_pair(&left, &right);
+
+        let proofs = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (_, allowance))| WhitelistProof {
+                allowance: *allowance,
+                proof: vec![
+                    Binary(leaves[i ^ 1].clone()),
+                    Binary(if i < 2 { right.clone() } else { left.clone() }),
+                ],
+            })
+            .collect();
+
+        (Binary(root), proofs)
+    }
+
+    fn expected_transfer(recipient: &str, amount: u128) -> CosmosMsg {
+        transfer_msg(
+            HumanAddr(recipient.to_string()),
+            Uint128(amount),
+            None,
+            None,
+            BLOCK_SIZE,
+            token_contract().token_code_hash,
+            token_contract().contract_addr,
+        )
+        .unwrap()
+    }
+
+    /// answers the nft contract's NftInfo query with the given public metadata
+    struct NftInfoQuerier {
+        public_metadata: Metadata,
+    }
+
+    impl Querier for NftInfoQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match request {
+                QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr, msg, ..
+                }) if contract_addr == nft_contract().contract_addr => {
+                    let NftsQueryMsg::NftInfo { .. } = from_binary(&msg).unwrap();
+                    Ok(to_binary(&NftsQueryAnswer::NftInfo {
+                        token_uri: self.public_metadata.token_uri.clone(),
+                        extension: self.public_metadata.extension.clone(),
+                    }))
+                }
+                _ => Err(SystemError::NoSuchContract {
+                    addr: HumanAddr("unknown".to_string()),
+                }),
+            }
+        }
+    }
+
+    fn with_nft_info_querier(
+        deps: Extern<MockStorage, MockApi, MockQuerier>,
+        public_metadata: Metadata,
+    ) -> Extern<MockStorage, MockApi, NftInfoQuerier> {
+        Extern {
+            storage: deps.storage,
+            api: deps.api,
+            querier: NftInfoQuerier { public_metadata },
+        }
+    }
+
+    fn stat(trait_type: &str, value: u32) -> Trait {
+        Trait {
+            display_type: None,
+            trait_type: Some(trait_type.to_string()),
+            value: value.to_string(),
+            max_value: None,
+        }
+    }
+
+    fn snail_metadata(attributes: Option<Vec<Trait>>) -> Metadata {
+        Metadata {
+            token_uri: None,
+            extension: Some(Extension {
+                name: Some("Secret Snail #1".to_string()),
+                attributes,
+                ..Extension::default()
+            }),
+        }
+    }
+
+    fn extract_set_metadata(messages: &[CosmosMsg]) -> (String, Option<Metadata>) {
+        match &messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(*contract_addr, nft_contract().contract_addr);
+                match from_binary(msg).unwrap() {
+                    NftsHandleMsg::SetMetadata {
+                        token_id,
+                        public_metadata,
+                        private_metadata,
+                        ..
+                    } => {
+                        assert_eq!(private_metadata, None);
+                        (token_id, public_metadata)
+                    }
+                    _ => panic!("Expected a SetMetadata message"),
+                }
+            }
+            _ => panic!("Expected a wasm execute message"),
+        }
+    }
+
+    /// mint_ready_helper with the commit-reveal mode enabled
+    fn commit_reveal_helper(token_count: u16) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], token_count);
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: Some(true),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        deps
+    }
+
+    fn extract_mints(message: &CosmosMsg) -> Vec<Mint> {
+        match message {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(*contract_addr, nft_contract().contract_addr);
+                match from_binary(msg).unwrap() {
+                    NftsHandleMsg::BatchMintNft { mints, .. } => mints,
+                    _ => panic!("Expected a BatchMintNft message"),
+                }
+            }
+            _ => panic!("Expected a wasm execute message"),
+        }
+    }
+
+    fn pending_mints<Q: Querier>(deps: &Extern<MockStorage, MockApi, Q>) -> Vec<PendingMintInfo> {
+        let query_answer: QueryAnswer =
+            from_binary(&query(deps, QueryMsg::PendingMints {}).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::PendingMints { pending_mints } => pending_mints,
+            _ => panic!("unexpected"),
+        }
+    }
+
+    fn phase(
+        name: &str,
+        start_time: u64,
+        end_time: Option<u64>,
+        price: u128,
+        max_per_wallet: Option<u16>,
+        allowed_addresses: Option<Vec<&str>>,
+    ) -> MintPhase {
+        MintPhase {
+            name: name.to_string(),
+            start_time,
+            end_time,
+            price: Uint128(price),
+            max_per_wallet,
+            max_per_tx: 2,
+            allowed_addresses: allowed_addresses.map(|addresses| {
+                addresses
+                    .into_iter()
+                    .map(|a| HumanAddr(a.to_string()))
+                    .collect()
+            }),
+        }
+    }
+
+    fn env_at(sender: &str, time: u64) -> Env {
+        let mut env = mock_env(sender, &[]);
+        env.block.time = time;
+        env
+    }
+
+    fn mint_phases<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+        current_time: u64,
+    ) -> (Option<String>, Vec<String>) {
+        let query_msg = QueryMsg::MintPhases { current_time };
+        let query_answer: QueryAnswer = from_binary(&query(deps, query_msg).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::MintPhases {
+                current_phase,
+                upcoming_phases,
+            } => (
+                current_phase.map(|p| p.name),
+                upcoming_phases.into_iter().map(|p| p.name).collect(),
+            ),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    fn extract_error_msg<T: Any>(error: StdResult<T>) -> String {
+        match error {
+            Ok(_response) => panic!("Expected error, but had Ok response"),
+            Err(err) => match err {
+                StdError::GenericErr { msg, .. } => msg,
+                _ => panic!("Unexpected error result {:?}", err),
+            },
+        }
+    }
+
+    // Init tests
+
+    #[test]
+    fn test_init_revenue_split_validation() {
+        // no recipients
+        let (init_result, _deps) = init_helper(vec![]);
+        let error = extract_error_msg(init_result);
+        assert!(error.contains("Revenue split needs at least one recipient"));
+
+        // below 100%
+        let (init_result, _deps) =
+            init_helper(vec![split("alice", 500_000), split("bob", 499_999)]);
+        let error = extract_error_msg(init_result);
+        assert!(error.contains("must add up to 1000000 (100%), got 999999"));
+
+        // above 100%
+        let (init_result, _deps) =
+            init_helper(vec![split("alice", 500_000), split("bob", 500_001)]);
+        let error = extract_error_msg(init_result);
+        assert!(error.contains("must add up to 1000000 (100%), got 1000001"));
+
+        // exactly 100%
+        let (init_result, _deps) = init_helper(vec![
+            split("alice", 333_334),
+            split("bob", 333_333),
+            split("charlie", 333_333),
+        ]);
+        assert!(init_result.is_ok());
+    }
+
+    // Revenue split tests
+
+    #[test]
+    fn test_revenue_split_amounts_odd_amounts() {
+        let revenue_split = vec![
+            split("alice", 333_334),
+            split("bob", 333_333),
+            split("charlie", 333_333),
+        ];
+
+        // dust goes to the first recipient
+        let shares = revenue_split_amounts(&revenue_split, Uint128(100));
+        assert_eq!(shares, vec![Uint128(34), Uint128(33), Uint128(33)]);
+
+        let shares = revenue_split_amounts(&revenue_split, Uint128(1));
+        assert_eq!(shares, vec![Uint128(1), Uint128(0), Uint128(0)]);
+
+        let shares = revenue_split_amounts(&revenue_split, Uint128(0));
+        assert_eq!(shares, vec![Uint128(0), Uint128(0), Uint128(0)]);
+
+        let shares = revenue_split_amounts(&revenue_split, Uint128(1_000_001));
+        assert_eq!(
+            shares,
+            vec![Uint128(333_335), Uint128(333_333), Uint128(333_333)]
+        );
+
+        // fractional percentages
+        let revenue_split = vec![split("alice", 2_500), split("bob", 997_500)];
+        let shares = revenue_split_amounts(&revenue_split, Uint128(7_777_777));
+        assert_eq!(shares, vec![Uint128(19_445), Uint128(7_758_332)]);
+
+        // amounts that would overflow a naive amount * percentage
+        let revenue_split = vec![split("alice", 999_999), split("bob", 1)];
+        let shares = revenue_split_amounts(&revenue_split, Uint128(u128::MAX));
+        assert_eq!(shares.iter().map(|s| s.u128()).sum::<u128>(), u128::MAX);
+        assert_eq!(shares[1], Uint128(u128::MAX / 1_000_000));
+    }
+
+    #[test]
+    fn test_revenue_split_amounts_many_recipients() {
+        let revenue_split: Vec<RevenueSplit> = (0..100)
+            .map(|i| split(&format!("recipient{}", i), 10_000))
+            .collect();
+
+        let shares = revenue_split_amounts(&revenue_split, Uint128(12_345));
+        assert_eq!(shares.len(), 100);
+        assert_eq!(shares[0], Uint128(168));
+        assert!(shares[1..].iter().all(|s| *s == Uint128(123)));
+        assert_eq!(shares.iter().map(|s| s.u128()).sum::<u128>(), 12_345);
+
+        let mut revenue_split: Vec<RevenueSplit> = (0..7)
+            .map(|i| split(&format!("recipient{}", i), 142_857))
+            .collect();
+        revenue_split[6].percentage += 1;
+
+        for amount in &[1u128, 7, 99, 1_000_003, 123_456_789] {
+            let shares = revenue_split_amounts(&revenue_split, Uint128(*amount));
+            assert_eq!(shares.iter().map(|s| s.u128()).sum::<u128>(), *amount);
+        }
+    }
+
+    #[test]
+    fn test_mint_revenue_split() {
+        let mut deps = mint_ready_helper(
+            vec![
+                split("alice", 333_334),
+                split("bob", 333_333),
+                split("charlie", 333_333),
+            ],
+            10,
+        );
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 3_000_000, 3),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[1], expected_transfer("alice", 1_000_002));
+        assert_eq!(messages[2], expected_transfer("bob", 999_999));
+        assert_eq!(messages[3], expected_transfer("charlie", 999_999));
+
+        // recipients with a zero share are not paid
+        let mut deps = mint_ready_helper(vec![split("alice", 999_999), split("bob", 1)], 10);
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: Some(Uint128(999)),
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 999, 1),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1], expected_transfer("alice", 999));
+    }
+
+    // Snail stats tests
+
+    #[test]
+    fn test_update_snail_stats() {
+        let deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let mut deps = with_nft_info_querier(
+            deps,
+            snail_metadata(Some(vec![stat("Wins", 2), stat("Loses", 5)])),
+        );
+
+        // not permitted
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 1,
+            loses_delta: 0,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only game operators can execute this action!"));
+
+        // even the admin needs permission
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only game operators can execute this action!"));
+
+        let permit_msg = HandleMsg::UpdateChangeMetadataPermitedAdresses {
+            change_metadata_permited_addresses: vec![HumanAddr("race".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), permit_msg).unwrap();
+
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(messages.len(), 1);
+        let (token_id, public_metadata) = extract_set_metadata(&messages);
+        assert_eq!(token_id, "1".to_string());
+        assert_eq!(
+            public_metadata,
+            Some(snail_metadata(Some(vec![
+                stat("Wins", 3),
+                stat("Loses", 5)
+            ])))
+        );
+
+        // missing traits are added
+        let mut deps = with_nft_info_querier(
+            Extern {
+                storage: deps.storage,
+                api: deps.api,
+                querier: MockQuerier::new(&[]),
+            },
+            snail_metadata(None),
+        );
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 0,
+            loses_delta: 4,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let (_, public_metadata) = extract_set_metadata(&handle_result.unwrap().messages);
+        assert_eq!(
+            public_metadata,
+            Some(snail_metadata(Some(vec![
+                stat("Wins", 0),
+                stat("Loses", 4)
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_update_snail_stats_errors() {
+        let deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let mut deps = with_nft_info_querier(
+            deps,
+            Metadata {
+                token_uri: None,
+                extension: None,
+            },
+        );
+        let permit_msg = HandleMsg::UpdateChangeMetadataPermitedAdresses {
+            change_metadata_permited_addresses: vec![HumanAddr("race".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), permit_msg).unwrap();
+
+        // token without on-chain metadata
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "404".to_string(),
+            wins_delta: 1,
+            loses_delta: 0,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Token 404 has no snail stats to update"));
+
+        // overflow
+        deps.querier.public_metadata =
+            snail_metadata(Some(vec![stat("Wins", u32::MAX), stat("Loses", 0)]));
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 1,
+            loses_delta: 0,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Wins trait overflow"));
+
+        // non numeric trait
+        let mut bad_wins = stat("Wins", 0);
+        bad_wins.value = "many".to_string();
+        deps.querier.public_metadata = snail_metadata(Some(vec![bad_wins, stat("Loses", 0)]));
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 1,
+            loses_delta: 0,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Wins trait has a non numeric value: many"));
+    }
+
+    // Commit-reveal tests
+
+    #[test]
+    fn test_commit_reveal_mint() {
+        let mut deps = commit_reveal_helper(5);
+
+        // the commit takes the payment but mints nothing yet
+        let handle_msg = HandleMsg::Receive {
+            sender: HumanAddr("buyer".to_string()),
+            from: HumanAddr("buyer".to_string()),
+            amount: Uint128(3_000_000),
+            msg: to_binary(&HandleReceiveMsg::MintNfts {
+                count: 3,
+                entropy: Some("buyer entropy".to_string()),
+                whitelist_proof: None,
+                partial_fill: None,
+            })
+            .unwrap(),
+        };
+        let handle_response = handle(&mut deps, mock_env("sscrt", &[]), handle_msg).unwrap();
+        assert_eq!(
+            handle_response.messages,
+            vec![expected_transfer("alice", 3_000_000)]
+        );
+        assert_eq!(handle_response.log, vec![log("pending_mint_id", 1)]);
+        let commit_height = mock_env("sscrt", &[]).block.height;
+        assert_eq!(
+            pending_mints(&deps),
+            vec![PendingMintInfo {
+                mint_id: 1,
+                count: 3,
+                commit_height,
+            }]
+        );
+
+        // reserved tokens can't be sold again
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer2", 3_000_000, 3),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Not enought tokens to be minted by this request!"));
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer2", 2_000_000, 2),
+        );
+        assert_eq!(handle_result.unwrap().log, vec![log("pending_mint_id", 2)]);
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer2", 1_000_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("All tokens have been minted"));
+
+        // not in the same block
+        let handle_msg = HandleMsg::RevealMints { mint_ids: vec![1] };
+        let handle_result = handle(&mut deps, mock_env("keeper", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains(&format!(
+            "Mint 1 can only be revealed after block {}",
+            commit_height
+        )));
+
+        // anyone can reveal, the tokens go to the buyer
+        let mut env = mock_env("keeper", &[]);
+        env.block.height += 1;
+        let handle_msg = HandleMsg::RevealMints { mint_ids: vec![1] };
+        let handle_response = handle(&mut deps, env.clone(), handle_msg).unwrap();
+        assert_eq!(handle_response.messages.len(), 1);
+        let mints = extract_mints(&handle_response.messages[0]);
+        assert_eq!(mints.len(), 3);
+        assert!(mints
+            .iter()
+            .all(|m| m.owner == Some(HumanAddr("buyer".to_string()))));
+        assert_eq!(pending_mints(&deps).len(), 1);
+
+        let handle_msg = HandleMsg::RevealMints { mint_ids: vec![1] };
+        let handle_result = handle(&mut deps, env.clone(), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Pending mint 1 not found"));
+
+        let handle_msg = HandleMsg::RevealMints { mint_ids: vec![2] };
+        let handle_response = handle(&mut deps, env, handle_msg).unwrap();
+        let mut token_ids: Vec<String> = mints
+            .into_iter()
+            .chain(extract_mints(&handle_response.messages[0]))
+            .map(|m| m.token_id.unwrap())
+            .collect();
+        token_ids.sort();
+        assert_eq!(token_ids, vec!["1", "2", "3", "4", "5"]);
+        assert!(pending_mints(&deps).is_empty());
+    }
+
+    #[test]
+    fn test_reveal_does_not_depend_on_revealer() {
+        let mut revealed = vec![];
+
+        for (revealer, blocks_later) in &[("buyer", 1), ("keeper", 1000)] {
+            let mut deps = commit_reveal_helper(10);
+            handle(
+                &mut deps,
+                mock_env("sscrt", &[]),
+                receive_msg("buyer", 3_000_000, 3),
+            )
+            .unwrap();
+
+            let mut env = mock_env(*revealer, &[]);
+            env.block.height += blocks_later;
+            env.block.time += blocks_later * 6;
+            let handle_msg = HandleMsg::RevealMints { mint_ids: vec![1] };
+            let handle_response = handle(&mut deps, env, handle_msg).unwrap();
+            let picks: Vec<(Option<String>, Option<Vec<HiddenAttribute>>)> =
+                extract_mints(&handle_response.messages[0])
+                    .into_iter()
+                    .map(|m| (m.token_id, m.hidden_attributes))
+                    .collect();
+            revealed.push(picks);
+        }
+
+        assert_eq!(revealed[0], revealed[1]);
+    }
+
+    // Mint phase tests
+
+    #[test]
+    fn test_set_mint_phases_validation() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![phase("public", 100, None, 1_000, None, None)],
+        };
+        let handle_result = handle(&mut deps, mock_env("buyer", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let invalid_schedules = vec![
+            (
+                vec![
+                    phase("og", 100, Some(200), 500, None, None),
+                    phase("public", 150, None, 1_000, None, None),
+                ],
+                "Mint phases og and public overlap",
+            ),
+            // phases are sorted by start time first
+            (
+                vec![
+                    phase("public", 200, None, 1_000, None, None),
+                    phase("og", 100, None, 500, None, None),
+                ],
+                "Mint phases og and public overlap",
+            ),
+            (
+                vec![phase("og", 100, Some(100), 500, None, None)],
+                "Mint phase og has to end after it starts",
+            ),
+            (
+                vec![
+                    phase("og", 100, Some(200), 500, None, None),
+                    phase("og", 200, None, 1_000, None, None),
+                ],
+                "Mint phase names must be unique",
+            ),
+        ];
+        for (phases, expected_error) in invalid_schedules {
+            let handle_msg = HandleMsg::SetMintPhases { phases };
+            let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+            let error = extract_error_msg(handle_result);
+            assert!(error.contains(expected_error), "{}", error);
+        }
+
+        let mut no_mints = phase("og", 100, None, 500, None, None);
+        no_mints.max_per_tx = 0;
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![no_mints],
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint phase og needs a max per tx of at least 1"));
+
+        // back to back phases are fine
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![
+                phase("og", 100, Some(200), 500, None, None),
+                phase("public", 200, None, 1_000, None, None),
+            ],
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        assert!(handle_result.is_ok());
+    }
+
+    #[test]
+    fn test_update_running_phase_keeps_caps() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let start = mock_env("admin", &[]).block.time + 10;
+        let schedule = |price: u128| HandleMsg::SetMintPhases {
+            phases: vec![
+                phase(
+                    "og",
+                    start,
+                    Some(start + 100),
+                    price,
+                    Some(2),
+                    Some(vec!["og"]),
+                ),
+                phase("public", start + 100, None, 1_000, None, None),
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), schedule(500)).unwrap();
+        handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("og", 1_000, 2),
+        )
+        .unwrap();
+
+        // only the price of the running phase changes, its wallet cap and total still hold
+        handle(&mut deps, env_at("admin", start + 1), schedule(400)).unwrap();
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 1),
+            receive_msg("og", 400, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint limit of the og phase reached, 0 left for this address"));
+        assert_eq!(mint_status(&deps).5[0].minted, 2);
+
+        // the allowed addresses are replaced with the schedule
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![phase("og", start, None, 400, Some(2), Some(vec!["friend"]))],
+        };
+        handle(&mut deps, env_at("admin", start + 2), handle_msg).unwrap();
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 2),
+            receive_msg("og", 400, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Address not allowed to mint in the og phase"));
+        handle(
+            &mut deps,
+            env_at("sscrt", start + 2),
+            receive_msg("friend", 400, 1),
+        )
+        .unwrap();
+        assert_eq!(mint_status(&deps).5[0].minted, 3);
+    }
+
+    #[test]
+    fn test_mint_phases() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let start = mock_env("admin", &[]).block.time + 10;
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![
+                phase(
+                    "og",
+                    start,
+                    Some(start + 100),
+                    500,
+                    Some(3),
+                    Some(vec!["og"]),
+                ),
+                phase("public", start + 100, None, 1_000, None, None),
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        assert_eq!(
+            mint_phases(&deps, start - 1),
+            (None, vec!["og".to_string(), "public".to_string()])
+        );
+        assert_eq!(
+            mint_phases(&deps, start + 99),
+            (Some("og".to_string()), vec!["public".to_string()])
+        );
+        assert_eq!(
+            mint_phases(&deps, start + 100),
+            (Some("public".to_string()), vec![])
+        );
+
+        // the UpdateMint flags are ignored while there are phases
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start - 1),
+            receive_msg("og", 500, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("No mint phase is active"));
+
+        // the nft contract can't change during a phase
+        let handle_msg = HandleMsg::AddNftContract {
+            contract: nft_contract(),
+            sealed_metadata: None,
+        };
+        let handle_result = handle(&mut deps, env_at("admin", start), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint should be stoped to perform this"));
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("buyer", 500, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Address not allowed to mint in the og phase"));
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("og", 1_000_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Incorrect amount of snip20 tokens received 1000000 != 500"));
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("og", 1_500, 3),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Requested mint count is too high, max is 2"));
+
+        // the wallet cap counts the buyer's mints, not the token contract's
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("og", 1_000, 2),
+        );
+        assert!(handle_result.is_ok());
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 50),
+            receive_msg("og", 1_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint limit of the og phase reached, 1 left for this address"));
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 50),
+            receive_msg("og", 500, 1),
+        );
+        assert!(handle_result.is_ok());
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 100),
+            receive_msg("buyer", 2_000, 2),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(messages[1], expected_transfer("alice", 2_000));
+
+        // without phases the UpdateMint settings apply again
+        let handle_msg = HandleMsg::SetMintPhases { phases: vec![] };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(mint_phases(&deps, start + 100), (None, vec![]));
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 100),
+            receive_msg("buyer", 1_000_000, 1),
+        );
+        assert!(handle_result.is_ok());
+    }
+
+    #[test]
+    fn test_mint_phase_info() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![phase("og", 100, Some(200), 500, Some(3), Some(vec!["og"]))],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let query_msg = QueryMsg::MintPhases { current_time: 0 };
+        let query_answer: QueryAnswer = from_binary(&query(&deps, query_msg).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::MintPhases {
+                upcoming_phases, ..
+            } => assert_eq!(
+                upcoming_phases,
+                vec![MintPhaseInfo {
+                    name: "og".to_string(),
+                    start_time: 100,
+                    end_time: Some(200),
+                    price: Uint128(500),
+                    max_per_wallet: Some(3),
+                    max_per_tx: 2,
+                    restricted: true,
+                }]
+            ),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    // Whitelist tests
+
+    #[test]
+    fn test_whitelist_allowances() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        let handle_msg = HandleMsg::AddToWhitelist {
+            entries: vec![(HumanAddr("buyer".to_string()), 3)],
+        };
+        let handle_result = handle(&mut deps, mock_env("buyer", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let handle_msg = HandleMsg::AddToWhitelist {
+            entries: vec![
+                (HumanAddr("wl".to_string()), 3),
+                (HumanAddr("removed".to_string()), 3),
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::RemoveFromWhitelist {
+            addresses: vec![HumanAddr("removed".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: true,
+            standard_mint_enabled: false,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        for address in ["buyer", "removed"].iter() {
+            let handle_result = handle(
+                &mut deps,
+                mock_env("sscrt", &[]),
+                receive_msg(address, 1_000_000, 1),
+            );
+            let error = extract_error_msg(handle_result);
+            assert!(
+                error.contains("Whitelist enabled only, buyer address not eligible for minting")
+            );
+        }
+
+        // the allowance is used per token, not per tx
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("wl", 2_000_000, 2),
+        );
+        assert!(handle_result.is_ok());
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("wl", 2_000_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("address can only mint 1 more tokens thought the whitelist"));
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("wl", 1_000_000, 1),
+        );
+        assert!(handle_result.is_ok());
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("wl", 1_000_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("address can only mint 0 more tokens thought the whitelist"));
+
+        // the admin can give more mints
+        let handle_msg = HandleMsg::AddToWhitelist {
+            entries: vec![(HumanAddr("wl".to_string()), 1)],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("wl", 1_000_000, 1),
+        );
+        assert!(handle_result.is_ok());
+    }
+
+    #[test]
+    fn test_merkle_whitelist() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let (root, proofs) = merkle_whitelist([("wl1", 2), ("wl2", 1), ("wl3", 5), ("wl4", 1)]);
+
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: true,
+            standard_mint_enabled: false,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("wl1", 1_000_000, 1, Some(proofs[0].clone())),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("No whitelist merkle root set"));
+
+        let handle_msg = HandleMsg::SetWhitelistMerkleRoot {
+            root: Some(root.clone()),
+        };
+        let handle_result = handle(&mut deps, mock_env("wl1", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let handle_msg = HandleMsg::SetWhitelistMerkleRoot {
+            root: Some(Binary(vec![1, 2, 3])),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Whitelist merkle root must be a 32 bytes sha256 hash"));
+
+        let handle_msg = HandleMsg::SetWhitelistMerkleRoot { root: Some(root) };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        // another address can't use the proof, and the allowance can't be raised
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("buyer", 1_000_000, 1, Some(proofs[0].clone())),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Invalid whitelist proof"));
+        let mut raised = proofs[1].clone();
+        raised.allowance = 5;
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("wl2", 1_000_000, 1, Some(raised)),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Invalid whitelist proof"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("wl1", 2_000_000, 2, Some(proofs[0].clone())),
+        );
+        assert!(handle_result.is_ok());
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("wl3", 1_000_000, 1, Some(proofs[2].clone())),
+        );
+        assert!(handle_result.is_ok());
+
+        // a used proof can't be replayed
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("wl1", 1_000_000, 1, Some(proofs[0].clone())),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("address can only mint 0 more tokens thought the whitelist"));
+
+        // without a proof the address is not on the stored whitelist
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("wl4", 1_000_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Whitelist enabled only, buyer address not eligible for minting"));
+    }
+
+    fn whitelist_allowance<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+        address: &str,
+        whitelist_proof: Option<WhitelistProof>,
+    ) -> (bool, u16) {
+        let query_result =
+            query_whitelist_allowance(deps, &HumanAddr(address.to_string()), whitelist_proof);
+        match from_binary(&query_result.unwrap()).unwrap() {
+            QueryAnswer::WhitelistAllowance {
+                whitelisted,
+                remaining,
+            } => (whitelisted, remaining),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    fn whitelist_page<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> (Vec<(String, u16)>, u32) {
+        let query_result = query_whitelist(deps, &HumanAddr("admin".to_string()), page, page_size);
+        match from_binary(&query_result.unwrap()).unwrap() {
+            QueryAnswer::Whitelist { entries, total } => (
+                entries
+                    .into_iter()
+                    .map(|WhitelistEntry { address, remaining }| (address.0, remaining))
+                    .collect(),
+                total,
+            ),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_whitelist_queries() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let (root, proofs) = merkle_whitelist([("wl1", 2), ("wl2", 1), ("wl3", 5), ("wl4", 1)]);
+
+        let handle_msg = HandleMsg::AddToWhitelist {
+            entries: vec![
+                (HumanAddr("a".to_string()), 2),
+                (HumanAddr("b".to_string()), 3),
+                (HumanAddr("c".to_string()), 1),
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::RemoveFromWhitelist {
+            addresses: vec![HumanAddr("b".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        // updating an allowance doesn't list the address twice
+        let handle_msg = HandleMsg::AddToWhitelist {
+            entries: vec![(HumanAddr("a".to_string()), 5)],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::SetWhitelistMerkleRoot { root: Some(root) };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        assert_eq!(
+            whitelist_page(&deps, None, None),
+            (vec![("a".to_string(), 5), ("c".to_string(), 1)], 2)
+        );
+        // the removed address keeps its slot, leaving its page empty
+        assert_eq!(whitelist_page(&deps, Some(1), Some(1)), (vec![], 2));
+        assert_eq!(
+            whitelist_page(&deps, Some(2), Some(1)),
+            (vec![("c".to_string(), 1)], 2)
+        );
+        assert_eq!(whitelist_page(&deps, Some(3), Some(1)), (vec![], 2));
+        // the offset of the last page fits in a 64 bits usize, the query must not abort
+        assert_eq!(
+            whitelist_page(&deps, Some(u32::MAX), Some(u32::MAX)),
+            (vec![], 2)
+        );
+        // whitelisting a removed address again puts it back in its slot
+        let handle_msg = HandleMsg::AddToWhitelist {
+            entries: vec![(HumanAddr("b".to_string()), 4)],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(
+            whitelist_page(&deps, Some(1), Some(1)),
+            (vec![("b".to_string(), 4)], 3)
+        );
+        let handle_msg = HandleMsg::RemoveFromWhitelist {
+            addresses: vec![HumanAddr("b".to_string()), HumanAddr("b".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(
+            whitelist_page(&deps, None, None),
+            (vec![("a".to_string(), 5), ("c".to_string(), 1)], 2)
+        );
+
+        let query_result = query_whitelist(&deps, &HumanAddr("a".to_string()), None, None);
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: true,
+            standard_mint_enabled: false,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("a", 2_000_000, 2),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            whitelist_receive_msg("wl3", 1_000_000, 1, Some(proofs[2].clone())),
+        )
+        .unwrap();
+
+        assert_eq!(whitelist_allowance(&deps, "a", None), (true, 3));
+        assert_eq!(whitelist_allowance(&deps, "b", None), (false, 0));
+        assert_eq!(
+            whitelist_allowance(&deps, "wl3", Some(proofs[2].clone())),
+            (true, 4)
+        );
+        assert_eq!(
+            whitelist_allowance(&deps, "wl4", Some(proofs[3].clone())),
+            (true, 1)
+        );
+        let query_result =
+            query_whitelist_allowance(&deps, &HumanAddr("b".to_string()), Some(proofs[3].clone()));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Invalid whitelist proof"));
+
+        // permits for other contracts are rejected before the query runs
+        let query_msg = QueryMsg::WithPermit {
+            permit: Permit {
+                params: PermitParams {
+                    allowed_tokens: vec![HumanAddr("othercontract".to_string())],
+                    permit_name: "whitelist".to_string(),
+                    chain_id: "secret-4".to_string(),
+                    permissions: vec![Permission::Owner],
+                },
+                signature: PermitSignature {
+                    pub_key: PubKey {
+                        r#type: "tendermint/PubKeySecp256k1".to_string(),
+                        value: Binary::from(vec![2u8; 33]),
+                    },
+                    signature: Binary::from(vec![0u8; 64]),
+                },
+            },
+            query: QueryWithPermit::WhitelistAllowance {
+                whitelist_proof: None,
+            },
+        };
+        let query_result = query(&deps, query_msg);
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Permit doesn't apply to token"));
+    }
+
+    // Native mint tests
+
+    fn native_mint_msg(count: u16) -> HandleMsg {
+        HandleMsg::MintNfts {
+            count,
+            entropy: None,
+            whitelist_proof: None,
+            partial_fill: None,
+        }
+    }
+
+    #[test]
+    fn test_native_mint() {
+        let mut deps = mint_ready_helper(vec![split("alice", 600_000), split("bob", 400_000)], 10);
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &coins(2_000_000, "uatom")),
+            native_mint_msg(2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only uscrt can be sent to mint"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &coins(1_000_000, "uscrt")),
+            native_mint_msg(2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Incorrect amount of uscrt received 1000000 != 2000000"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &coins(1_000_000, "uscrt")),
+            native_mint_msg(0),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("At least one token must be minted"));
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 0, 0),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("At least one token must be minted"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &coins(2_000_000, "uscrt")),
+            native_mint_msg(2),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        match &messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(msg).unwrap() {
+                NftsHandleMsg::BatchMintNft { mints, .. } => {
+                    assert_eq!(mints.len(), 2);
+                    assert!(mints
+                        .iter()
+                        .all(|mint| mint.owner == Some(HumanAddr("buyer".to_string()))));
+                }
+                _ => panic!("unexpected"),
+            },
+            _ => panic!("unexpected"),
+        }
+        let expected_split: Vec<CosmosMsg> = vec![("alice", 1_200_000), ("bob", 800_000)]
+            .into_iter()
+            .map(|(recipient, amount)| {
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: HumanAddr(MOCK_CONTRACT_ADDR.to_string()),
+                    to_address: HumanAddr(recipient.to_string()),
+                    amount: coins(amount, "uscrt"),
+                })
+            })
+            .collect();
+        assert_eq!(messages[1..].to_vec(), expected_split);
+    }
+
+    #[test]
+    fn test_mint_amount_overflow() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        let mut sent_funds = coins(u128::MAX, "uscrt");
+        sent_funds.extend(coins(1, "uscrt"));
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &sent_funds),
+            native_mint_msg(1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Sent funds overflow"));
+
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: Some(Uint128(u128::MAX)),
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", u128::MAX, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint price overflow"));
+        assert_eq!(mint_status(&deps).4, 10);
+    }
+
+    #[test]
+    fn test_native_mint_disabled() {
+        let mut deps = mock_dependencies(20, &[]);
+        let init_msg = InitMsg {
+            admin: Some(HumanAddr("admin".to_string())),
+            token_contract: token_contract(),
+            entropy: "We're going to need a bigger boat".to_string(),
+            mint_price: Uint128(1_000_000),
+            max_mint_per_tx: 5,
+            whitelist: vec![],
+            whitelist_merkle_root: None,
+            native_denom: None,
+            max_mint_per_wallet: None,
+            revenue_split: vec![split("alice", 1_000_000)],
+            nft_contract_init: None,
+        };
+        init(&mut deps, mock_env("instantiator", &[]), init_msg).unwrap();
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &coins(1_000_000, "uscrt")),
+            native_mint_msg(1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Native mint is not enabled"));
+    }
+
+    // Payment token tests
+
+    fn shd_contract() -> SecretContract {
+        SecretContract {
+            contract_addr: HumanAddr("shd".to_string()),
+            token_code_hash: "shd_hash".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_payment_tokens() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        let handle_msg = HandleMsg::AddPaymentToken {
+            token: shd_contract(),
+            price: Uint128(3_000),
+        };
+        let handle_result = handle(&mut deps, mock_env("buyer", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let handle_msg = HandleMsg::AddPaymentToken {
+            token: token_contract(),
+            price: Uint128(3_000),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The token contract is already accepted at the mint price"));
+
+        let handle_msg = HandleMsg::AddPaymentToken {
+            token: shd_contract(),
+            price: Uint128(3_000),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(
+            messages,
+            vec![register_receive_msg(
+                mock_env("admin", &[]).contract_code_hash,
+                None,
+                1,
+                "shd_hash".to_string(),
+                HumanAddr("shd".to_string()),
+            )
+            .unwrap()]
+        );
+
+        // changing the price doesn't register again
+        let handle_msg = HandleMsg::AddPaymentToken {
+            token: shd_contract(),
+            price: Uint128(2_000),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        assert_eq!(handle_result.unwrap().messages, vec![]);
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("shd", &[]),
+            receive_msg("buyer", 2_000_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Incorrect amount of snip20 tokens received 2000000 != 4000"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("shd", &[]),
+            receive_msg("buyer", 4_000, 2),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(
+            messages[1],
+            transfer_msg(
+                HumanAddr("alice".to_string()),
+                Uint128(4_000),
+                None,
+                None,
+                BLOCK_SIZE,
+                "shd_hash".to_string(),
+                HumanAddr("shd".to_string()),
+            )
+            .unwrap()
+        );
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("usdc", &[]),
+            receive_msg("buyer", 4_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Invalid token sent!"));
+
+        let handle_msg = HandleMsg::RemovePaymentToken {
+            contract_addr: HumanAddr("shd".to_string()),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env("shd", &[]),
+            receive_msg("buyer", 4_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Invalid token sent!"));
+
+        let handle_msg = HandleMsg::RemovePaymentToken {
+            contract_addr: HumanAddr("shd".to_string()),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("shd is not an accepted payment token"));
+    }
+
+    // Partial fill tests
+
+    #[test]
+    fn test_partial_fill_refunds() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        // exact mints keep failing on a wrong amount
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 2_500_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Incorrect amount of snip20 tokens received 2500000 != 2000000"));
+
+        // overpayment
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            partial_receive_msg("buyer", 2_500_000, 2),
+        );
+        let HandleResponse {
+            messages,
+            log: logs,
+            ..
+        } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 2);
+        assert_eq!(
+            messages[1..].to_vec(),
+            vec![
+                expected_transfer("alice", 2_000_000),
+                expected_transfer("buyer", 500_000)
+            ]
+        );
+        assert_eq!(logs, vec![log("refund", 500_000)]);
+
+        // underpayment mints what was paid for
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            partial_receive_msg("buyer", 2_500_000, 3),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 2);
+        assert_eq!(messages[2], expected_transfer("buyer", 500_000));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            partial_receive_msg("buyer", 500_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Incorrect amount of snip20 tokens received 500000 != 1000000"));
+
+        // max per tx of 5
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            partial_receive_msg("buyer", 7_000_000, 7),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 5);
+        assert_eq!(messages[2], expected_transfer("buyer", 2_000_000));
+
+        // only 1 token left
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            partial_receive_msg("buyer", 3_000_000, 3),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 1);
+        assert_eq!(messages[2], expected_transfer("buyer", 2_000_000));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            partial_receive_msg("buyer", 1_000_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("All tokens have been minted"));
+    }
+
+    #[test]
+    fn test_partial_fill_limits() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let start = mock_env("admin", &[]).block.time;
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![phase("og", start, None, 500, Some(3), None)],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        // cut to the max per tx of the phase
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            partial_receive_msg("buyer", 1_500, 3),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 2);
+        assert_eq!(messages[2], expected_transfer("buyer", 500));
+
+        // cut to what is left of the wallet cap
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            partial_receive_msg("buyer", 1_000, 2),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 1);
+        assert_eq!(messages[2], expected_transfer("buyer", 500));
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            partial_receive_msg("buyer", 500, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint limit of the og phase reached, 0 left for this address"));
+
+        // native mints are refunded in the native coin
+        let handle_msg = HandleMsg::MintNfts {
+            count: 2,
+            entropy: None,
+            whitelist_proof: None,
+            partial_fill: Some(true),
+        };
+        let handle_result = handle(
+            &mut deps,
+            Env {
+                message: mock_env("native", &coins(1_200, "uscrt")).message,
+                ..env_at("native", start)
+            },
+            handle_msg,
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 2);
+        assert_eq!(
+            messages[2],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr(MOCK_CONTRACT_ADDR.to_string()),
+                to_address: HumanAddr("native".to_string()),
+                amount: coins(200, "uscrt"),
+            })
+        );
+    }
+
+    // Wallet cap tests
+
+    fn wallet_mints<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+        address: &str,
+    ) -> (u16, Option<u16>) {
+        let query_result = query_wallet_mints(deps, &HumanAddr(address.to_string()));
+        match from_binary(&query_result.unwrap()).unwrap() {
+            QueryAnswer::WalletMints { minted, remaining } => (minted, remaining),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_wallet_cap() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        // mints before the cap is set count against it
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        )
+        .unwrap();
+        assert_eq!(wallet_mints(&deps, "buyer"), (1, None));
+
+        let handle_msg = HandleMsg::SetMaxMintPerWallet {
+            max_mint_per_wallet: Some(4),
+        };
+        let handle_result = handle(&mut deps, mock_env("buyer", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+        let handle_msg = HandleMsg::SetMaxMintPerWallet {
+            max_mint_per_wallet: Some(4),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(wallet_mints(&deps, "buyer"), (1, Some(3)));
+
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 2_000_000, 2),
+        )
+        .unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 2_000_000, 2),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint limit per wallet reached, 1 left for this address"));
+
+        // the cap holds whatever the buyer pays with
+        handle(
+            &mut deps,
+            mock_env("buyer", &coins(1_000_000, "uscrt")),
+            native_mint_msg(1),
+        )
+        .unwrap();
+        assert_eq!(wallet_mints(&deps, "buyer"), (4, Some(0)));
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint limit per wallet reached, 0 left for this address"));
+
+        // other addresses have their own cap
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("other", 4_000_000, 4),
+        );
+        assert!(handle_result.is_ok());
+        assert_eq!(wallet_mints(&deps, "other"), (4, Some(0)));
+
+        let handle_msg = HandleMsg::SetMaxMintPerWallet {
+            max_mint_per_wallet: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        );
+        assert!(handle_result.is_ok());
+        assert_eq!(wallet_mints(&deps, "buyer"), (5, None));
+    }
+
+    // Dutch auction tests
+
+    fn auction(start_time: u64, rebate: bool) -> DutchAuction {
+        DutchAuction {
+            start_time,
+            end_time: start_time + 100,
+            start_price: Uint128(1_000),
+            floor_price: Uint128(400),
+            step_duration: 10,
+            rebate,
+        }
+    }
+
+    fn mint_price<Q: Querier>(deps: &Extern<MockStorage, MockApi, Q>, current_time: u64) -> u128 {
+        let query_msg = QueryMsg::MintPrice { current_time };
+        match from_binary(&query(deps, query_msg).unwrap()).unwrap() {
+            QueryAnswer::MintPrice { price, .. } => price.u128(),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_dutch_auction_price() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let start = mock_env("admin", &[]).block.time;
+
+        let handle_msg = HandleMsg::SetDutchAuction {
+            auction: Some(auction(start, false)),
+        };
+        let handle_result = handle(&mut deps, mock_env("buyer", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let mut no_steps = auction(start, false);
+        no_steps.step_duration = 0;
+        let mut no_duration = auction(start, false);
+        no_duration.end_time = start;
+        let mut high_floor = auction(start, false);
+        high_floor.floor_price = Uint128(2_000);
+        let invalid_auctions = vec![
+            (
+                no_steps,
+                "Dutch auction steps need a duration of at least 1 second",
+            ),
+            (no_duration, "Dutch auction has to end after it starts"),
+            (
+                high_floor,
+                "Dutch auction floor price can't be higher than its start price",
+            ),
+        ];
+        for (invalid_auction, expected_error) in invalid_auctions {
+            let handle_msg = HandleMsg::SetDutchAuction {
+                auction: Some(invalid_auction),
+            };
+            let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+            let error = extract_error_msg(handle_result);
+            assert!(error.contains(expected_error));
+        }
+
+        let handle_msg = HandleMsg::SetDutchAuction {
+            auction: Some(auction(start, false)),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        assert_eq!(mint_price(&deps, start - 5), 1_000);
+        assert_eq!(mint_price(&deps, start + 9), 1_000);
+        assert_eq!(mint_price(&deps, start + 10), 940);
+        assert_eq!(mint_price(&deps, start + 55), 700);
+        assert_eq!(mint_price(&deps, start + 100), 400);
+        assert_eq!(mint_price(&deps, start + 1_000), 400);
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 55),
+            receive_msg("buyer", 1_000, 1),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Incorrect amount of snip20 tokens received 1000 != 700"));
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start + 55),
+            receive_msg("buyer", 1_400, 2),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(messages[1], expected_transfer("alice", 1_400));
+
+        // without rebates there is nothing to claim
+        let handle_result = handle(
+            &mut deps,
+            env_at("buyer", start + 100),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The dutch auction has no rebates"));
+
+        let handle_msg = HandleMsg::SetDutchAuction { auction: None };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(mint_price(&deps, start + 55), 1_000_000);
+        let handle_result = handle(
+            &mut deps,
+            env_at("buyer", start + 100),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("No dutch auction set"));
+    }
+
+    #[test]
+    fn test_dutch_auction_rebate() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 3);
+        let start = mock_env("admin", &[]).block.time;
+        let handle_msg = HandleMsg::SetDutchAuction {
+            auction: Some(auction(start, true)),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        // only the floor price is paid out at mint
+        let handle_result = handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("first", 1_000, 1),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(
+            messages[1..].to_vec(),
+            vec![expected_transfer("alice", 400)]
+        );
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("first", start + 50),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The dutch auction is not over yet"));
+
+        handle(
+            &mut deps,
+            env_at("sscrt", start + 50),
+            receive_msg("second", 700, 1),
+        )
+        .unwrap();
+        // the last token sells out the collection at the clearing price of 520
+        handle(
+            &mut deps,
+            env_at("sscrt", start + 80),
+            receive_msg("first", 520, 1),
+        )
+        .unwrap();
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("first", start + 80),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                expected_transfer("first", 480),
+                expected_transfer("alice", 240)
+            ]
+        );
+        let handle_result = handle(
+            &mut deps,
+            env_at("first", start + 80),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("No dutch auction mints to claim a rebate for"));
+
+        let handle_result = handle(
+            &mut deps,
+            env_at("second", start + 200),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                expected_transfer("second", 180),
+                expected_transfer("alice", 120)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dutch_auction_change_waits_for_claims() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 3);
+        let start = mock_env("admin", &[]).block.time;
+        let handle_msg = HandleMsg::SetDutchAuction {
+            auction: Some(auction(start, true)),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("first", 1_000, 1),
+        )
+        .unwrap();
+
+        // neither a new auction nor its removal can strand the rebate
+        let replace_msg = HandleMsg::SetDutchAuction {
+            auction: Some(DutchAuction {
+                floor_price: Uint128(100),
+                ..auction(start + 200, false)
+            }),
+        };
+        let handle_result = handle(&mut deps, env_at("admin", start + 200), replace_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains(
+            "The dutch auction can't be changed while 1 purchases have rebates to claim"
+        ));
+        let remove_msg = HandleMsg::SetDutchAuction { auction: None };
+        let handle_result = handle(&mut deps, env_at("admin", start + 200), remove_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("can't be changed while 1 purchases have rebates to claim"));
+
+        // the claim uses the auction the purchase was made in
+        let handle_result = handle(
+            &mut deps,
+            env_at("first", start + 200),
+            HandleMsg::ClaimAuctionRebate {},
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(messages, vec![expected_transfer("first", 600)]);
+
+        handle(&mut deps, env_at("admin", start + 200), replace_msg).unwrap();
+    }
+
+    #[test]
+    fn test_withdraw_keeps_auction_rebates() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 3);
+        let start = mock_env("admin", &[]).block.time;
+        let handle_msg = HandleMsg::SetDutchAuction {
+            auction: Some(auction(start, true)),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("first", 1_000, 1),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            env_at("sscrt", start + 50),
+            receive_msg("second", 700, 1),
+        )
+        .unwrap();
+
+        // 900 over the floor price are kept until the buyers claim them
+        let mut deps = Extern {
+            storage: deps.storage,
+            api: deps.api,
+            querier: BalanceQuerier {
+                bank: MockQuerier::new(&[]),
+                key: "minter_key".to_string(),
+                amount: Uint128(1_000),
+            },
+        };
+        let withdraw_msg = |amount: u128| HandleMsg::WithdrawToken {
+            token: token_contract(),
+            amount: Uint128(amount),
+            recipient: HumanAddr("treasury".to_string()),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), withdraw_msg(100));
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Set a viewing key on the token with SetTokenViewingKey"));
+
+        let handle_msg = HandleMsg::SetTokenViewingKey {
+            token: token_contract(),
+            key: "minter_key".to_string(),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), withdraw_msg(101));
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains(
+            "Only 100 snip20 tokens can be withdrawn, 900 are kept for dutch auction rebates"
+        ));
+        handle(&mut deps, mock_env("admin", &[]), withdraw_msg(100)).unwrap();
+
+        // a claim only releases what was kept for that buyer
+        handle(
+            &mut deps,
+            env_at("first", start + 200),
+            HandleMsg::ClaimAuctionRebate {},
+        )
+        .unwrap();
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), withdraw_msg(701));
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only 700 snip20 tokens can be withdrawn, 300 are kept"));
+
+        handle(
+            &mut deps,
+            env_at("second", start + 200),
+            HandleMsg::ClaimAuctionRebate {},
+        )
+        .unwrap();
+        handle(&mut deps, mock_env("admin", &[]), withdraw_msg(1_000)).unwrap();
+    }
+
+    // Mint status tests
+
+    fn mint_status<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+    ) -> (
+        Option<SecretContract>,
+        u32,
+        u32,
+        u32,
+        u32,
+        Vec<PhaseMintStatus>,
+    ) {
+        match from_binary(&query(deps, QueryMsg::MintStatus {}).unwrap()).unwrap() {
+            QueryAnswer::MintStatus {
+                nft_contract,
+                total_loaded,
+                minted,
+                pending,
+                remaining,
+                phases,
+            } => (
+                nft_contract,
+                total_loaded,
+                minted,
+                pending,
+                remaining,
+                phases,
+            ),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_mint_status_without_nft_contract() {
+        let (init_result, deps) = init_helper(vec![split("alice", 1_000_000)]);
+        init_result.unwrap();
+
+        assert_eq!(mint_status(&deps), (None, 0, 0, 0, 0, vec![]));
+        match from_binary(&query(&deps, QueryMsg::Info {}).unwrap()).unwrap() {
+            QueryAnswer::Info {
+                nft_contract,
+                mint_current_count,
+                ..
+            } => {
+                assert_eq!(nft_contract, None);
+                assert_eq!(mint_current_count, 0);
+            }
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_mint_status() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let start = mock_env("admin", &[]).block.time;
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![
+                phase("og", start, Some(start + 100), 500, None, None),
+                phase("public", start + 100, None, 1_000, None, None),
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        handle(
+            &mut deps,
+            env_at("sscrt", start),
+            receive_msg("og", 1_000, 2),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            env_at("sscrt", start + 100),
+            receive_msg("buyer", 1_000, 1),
+        )
+        .unwrap();
+
+        let phase_status = |name: &str, start_time, end_time, minted| PhaseMintStatus {
+            name: name.to_string(),
+            start_time,
+            end_time,
+            minted,
+        };
+        assert_eq!(
+            mint_status(&deps),
+            (
+                Some(nft_contract()),
+                10,
+                3,
+                0,
+                7,
+                vec![
+                    phase_status("og", start, Some(start + 100), 2),
+                    phase_status("public", start + 100, None, 1),
+                ]
+            )
+        );
+
+        // committed mints are pending until they are revealed
+        let mut deps = commit_reveal_helper(5);
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 3_000_000, 3),
+        )
+        .unwrap();
+        assert_eq!(
+            mint_status(&deps),
+            (Some(nft_contract()), 5, 0, 3, 2, vec![])
+        );
+
+        let mut env = mock_env("buyer", &[]);
+        env.block.height += 1;
+        let handle_msg = HandleMsg::RevealMints { mint_ids: vec![1] };
+        handle(&mut deps, env, handle_msg).unwrap();
+        assert_eq!(
+            mint_status(&deps),
+            (Some(nft_contract()), 5, 3, 0, 2, vec![])
+        );
+    }
+
+    // NFT contract instantiation tests
+
+    #[test]
+    fn test_init_nft_contract() {
+        let mut deps = mock_dependencies(20, &[]);
+        let init_msg = InitMsg {
+            admin: Some(HumanAddr("admin".to_string())),
+            token_contract: token_contract(),
+            entropy: "We're going to need a bigger boat".to_string(),
+            mint_price: Uint128(1_000_000),
+            max_mint_per_tx: 5,
+            whitelist: vec![],
+            whitelist_merkle_root: None,
+            native_denom: None,
+            max_mint_per_wallet: None,
+            revenue_split: vec![split("alice", 1_000_000)],
+            nft_contract_init: Some(NftContractInit {
+                code_id: 7,
+                code_hash: "snails_hash".to_string(),
+                label: "snails".to_string(),
+                name: "Secret Snails".to_string(),
+                symbol: "SNAIL".to_string(),
+                royalty_info: None,
+                config: None,
+                hidden_attribute_viewers: Some(vec![HumanAddr("race".to_string())]),
+            }),
+        };
+        let mut env = mock_env("instantiator", &[]);
+        env.contract_code_hash = "minter_hash".to_string();
+        let init_response = init(&mut deps, env, init_msg).unwrap();
+
+        assert_eq!(init_response.messages.len(), 2);
+        match &init_response.messages[1] {
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id,
+                callback_code_hash,
+                msg,
+                label,
+                ..
+            }) => {
+                assert_eq!(*code_id, 7);
+                assert_eq!(callback_code_hash, "snails_hash");
+                assert_eq!(label, "snails");
+                let nft_init_msg: NftsInitMsg = from_binary(msg).unwrap();
+                assert_eq!(nft_init_msg.admin, Some(HumanAddr("admin".to_string())));
+                assert_eq!(
+                    nft_init_msg.minters,
+                    Some(vec![HumanAddr(MOCK_CONTRACT_ADDR.to_string())])
+                );
+                assert_eq!(
+                    nft_init_msg.hidden_attribute_viewers,
+                    Some(vec![HumanAddr("race".to_string())])
+                );
+                let callback = nft_init_msg.post_init_callback.unwrap();
+                assert_eq!(
+                    callback.contract_address,
+                    HumanAddr(MOCK_CONTRACT_ADDR.to_string())
+                );
+                assert_eq!(callback.code_hash, "minter_hash");
+                assert_eq!(
+                    from_binary::<HandleMsg>(&callback.msg).unwrap(),
+                    HandleMsg::RegisterNftContract {}
+                );
+            }
+            _ => panic!("unexpected"),
+        }
+
+        // the new contract calls back with its address
+        let handle_msg = HandleMsg::RegisterNftContract {};
+        handle(&mut deps, mock_env("snails", &[]), handle_msg).unwrap();
+        assert_eq!(mint_status(&deps).0, Some(nft_contract()));
+
+        let handle_msg = HandleMsg::RegisterNftContract {};
+        let handle_result = handle(&mut deps, mock_env("impostor", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("No NFT contract is being instantiated"));
+        assert_eq!(mint_status(&deps).0, Some(nft_contract()));
+    }
+
+    #[test]
+    fn test_register_nft_contract_without_init() {
+        let (init_result, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        assert_eq!(init_result.unwrap().messages.len(), 1);
+
+        let handle_msg = HandleMsg::RegisterNftContract {};
+        let handle_result = handle(&mut deps, mock_env("impostor", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("No NFT contract is being instantiated"));
+        assert_eq!(mint_status(&deps).0, None);
+    }
+
+    #[test]
+    fn test_race_setup_on_instantiated_nft_contract() {
+        let mut deps = mock_dependencies(20, &[]);
+        let init_msg = InitMsg {
+            admin: Some(HumanAddr("admin".to_string())),
+            token_contract: token_contract(),
+            entropy: "We're going to need a bigger boat".to_string(),
+            mint_price: Uint128(1_000_000),
+            max_mint_per_tx: 5,
+            whitelist: vec![],
+            whitelist_merkle_root: None,
+            native_denom: None,
+            max_mint_per_wallet: None,
+            revenue_split: vec![split("alice", 1_000_000)],
+            nft_contract_init: Some(NftContractInit {
+                code_id: 7,
+                code_hash: "snails_hash".to_string(),
+                label: "snails".to_string(),
+                name: "Secret Snails".to_string(),
+                symbol: "SNAIL".to_string(),
+                royalty_info: None,
+                config: None,
+                hidden_attribute_viewers: None,
+            }),
+        };
+        let init_response = init(&mut deps, mock_env("instantiator", &[]), init_msg).unwrap();
+        let nft_init_msg: secret_snails_nft::msg::InitMsg = match &init_response.messages[1] {
+            CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => from_binary(msg).unwrap(),
+            _ => panic!("unexpected"),
+        };
+
+        // the NFT contract is instantiated by the minter
+        let mut nft_deps = mock_dependencies(20, &[]);
+        let mut nft_env = mock_env(MOCK_CONTRACT_ADDR, &[]);
+        nft_env.contract.address = HumanAddr("snails".to_string());
+        secret_snails_nft::contract::init(&mut nft_deps, nft_env, nft_init_msg).unwrap();
+        handle(
+            &mut deps,
+            mock_env("snails", &[]),
+            HandleMsg::RegisterNftContract {},
+        )
+        .unwrap();
+
+        // the minter's admin makes the race a hidden attribute viewer of the NFT contract...
+        let nft_handle_msg = secret_snails_nft::msg::HandleMsg::AddHiddenAttributeViewers {
+            viewers: vec![HumanAddr("race".to_string())],
+            padding: None,
+        };
+        secret_snails_nft::contract::handle(&mut nft_deps, mock_env("admin", &[]), nft_handle_msg)
+            .unwrap();
+        let viewers = secret_snails_nft::contract::query(
+            &nft_deps,
+            secret_snails_nft::msg::QueryMsg::HiddenAttributeViewers {},
+        )
+        .unwrap();
+        match from_binary(&viewers).unwrap() {
+            secret_snails_nft::msg::QueryAnswer::HiddenAttributeViewers { viewers } => {
+                assert_eq!(viewers, vec![HumanAddr("race".to_string())]);
+            }
+            _ => panic!("unexpected"),
+        }
+
+        // ...and a game operator of the minter
+        let handle_msg = HandleMsg::GrantRole {
+            role: Role::GameOperator,
+            address: HumanAddr("race".to_string()),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        // the minter mints and updates the tokens as one of the NFT minters
+        let minters = secret_snails_nft::contract::query(
+            &nft_deps,
+            secret_snails_nft::msg::QueryMsg::Minters {},
+        )
+        .unwrap();
+        match from_binary(&minters).unwrap() {
+            secret_snails_nft::msg::QueryAnswer::Minters { minters } => {
+                assert_eq!(
+                    minters,
+                    vec![
+                        HumanAddr("admin".to_string()),
+                        HumanAddr(MOCK_CONTRACT_ADDR.to_string())
+                    ]
+                );
+            }
+            _ => panic!("unexpected"),
+        }
+        let nft_handle_msg = secret_snails_nft::msg::HandleMsg::MintNft {
+            token_id: Some("1".to_string()),
+            owner: Some(HumanAddr("buyer".to_string())),
+            public_metadata: None,
+            private_metadata: None,
+            serial_number: None,
+            royalty_info: None,
+            transferable: None,
+            memo: None,
+            padding: None,
+        };
+        secret_snails_nft::contract::handle(
+            &mut nft_deps,
+            mock_env(MOCK_CONTRACT_ADDR, &[]),
+            nft_handle_msg,
+        )
+        .unwrap();
+    }
+
+    // Metadata preload tests
+
+    fn preload(id: &str, img_url: &str) -> PreLoad {
+        PreLoad {
+            id: id.to_string(),
+            img_url: img_url.to_string(),
+            attributes: None,
+            priv_attributes: None,
+            hidden_attributes: None,
+        }
+    }
+
+    fn provenance<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+    ) -> (Option<Binary>, u32, bool) {
+        match from_binary(&query(deps, QueryMsg::Provenance {}).unwrap()).unwrap() {
+            QueryAnswer::Provenance {
+                hash,
+                total_loaded,
+                locked,
+            } => (hash, total_loaded, locked),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_load_metadata_validation() {
+        let (init_result, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        init_result.unwrap();
+
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: vec![preload("1", "https://snails.io/1.gif")],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let invalid_data = vec![
+            (
+                vec![preload("", "https://snails.io/2.gif")],
+                "Token id can't be empty",
+            ),
+            (vec![preload("2", "")], "Token 2 has no image url"),
+            (
+                vec![preload("1", "https://snails.io/1.gif")],
+                "Token id 1 is already loaded",
+            ),
+            (
+                vec![
+                    preload("3", "https://snails.io/3.gif"),
+                    preload("3", "https://snails.io/3.gif"),
+                ],
+                "Token id 3 is already loaded",
+            ),
+        ];
+        for (new_data, expected_error) in invalid_data {
+            let handle_msg = HandleMsg::LoadMetadata { new_data };
+            let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+            let error = extract_error_msg(handle_result);
+            assert!(error.contains(expected_error));
+        }
+
+        // the pool is kept under its own prefix, away from the other keys
+        assert_eq!(deps.storage.get(&1u16.to_le_bytes()), None);
+    }
+
+    #[test]
+    fn test_provenance_and_lock() {
+        let (init_result, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        init_result.unwrap();
+        assert_eq!(provenance(&deps), (None, 0, false));
+
+        let tokens: Vec<PreLoad> = (1..=3)
+            .map(|id| preload(&id.to_string(), &format!("https://snails.io/{}.gif", id)))
+            .collect();
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: tokens[..2].to_vec(),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: tokens[2..].to_vec(),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        // loading in chunks commits to the same hash as loading everything at once
+        let expected_hash = tokens
+            .iter()
+            .fold(vec![], |hash, token| preload_hash(&hash, token).unwrap());
+        assert_eq!(
+            provenance(&deps),
+            (Some(Binary(expected_hash.clone())), 3, false)
+        );
+
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(
+            provenance(&deps),
+            (Some(Binary(expected_hash.clone())), 3, true)
+        );
+
+        // stopping the mint doesn't unlock the loading
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: false,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: vec![preload("4", "https://snails.io/4.gif")],
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Metadata can't be loaded once minting has been enabled"));
+        assert_eq!(provenance(&deps), (Some(Binary(expected_hash)), 3, true));
+    }
+
+    #[test]
+    fn test_mint_phases_lock_metadata() {
+        let (init_result, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        init_result.unwrap();
+        let start = mock_env("admin", &[]).block.time + 10;
+        let handle_msg = HandleMsg::SetMintPhases {
+            phases: vec![phase("public", start, None, 1_000, None, None)],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: vec![preload("1", "https://snails.io/1.gif")],
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Metadata can't be loaded once minting has been enabled"));
+    }
+
+    fn provenance_proof<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+        page: u32,
+        page_size: u32,
+    ) -> StdResult<(Vec<String>, u32)> {
+        let query_msg = QueryMsg::ProvenanceProof {
+            page: Some(page),
+            page_size: Some(page_size),
+        };
+        match from_binary(&query(deps, query_msg)?)? {
+            QueryAnswer::ProvenanceProof { tokens, total } => {
+                Ok((tokens.into_iter().map(|token| token.id).collect(), total))
+            }
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_provenance_proof() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 3);
+
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 2_000_000, 2),
+        )
+        .unwrap();
+        let error = extract_error_msg(provenance_proof(&deps, 0, 30));
+        assert!(error.contains("The provenance proof is only available once every token is minted"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        assert_eq!(extract_mints(&messages[0]).len(), 1);
+
+        // the proof keeps the load order, whatever order the tokens were minted in
+        assert_eq!(
+            provenance_proof(&deps, 0, 2).unwrap(),
+            (vec!["1".to_string(), "2".to_string()], 3)
+        );
+        assert_eq!(
+            provenance_proof(&deps, 1, 2).unwrap(),
+            (vec!["3".to_string()], 3)
+        );
+        let error = extract_error_msg(provenance_proof(&deps, u32::MAX, u32::MAX));
+        assert!(error.contains("Page is out of range"));
+
+        let expected_hash = (1..=3)
+            .map(|id| preload(&id.to_string(), &format!("https://snails.io/{}.gif", id)))
+            .fold(vec![], |hash, token| preload_hash(&hash, &token).unwrap());
+        assert_eq!(provenance(&deps), (Some(Binary(expected_hash)), 3, true));
+    }
+
+    #[test]
+    fn test_sealed_metadata_mint() {
+        let (init_result, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        init_result.unwrap();
+        let handle_msg = HandleMsg::AddNftContract {
+            contract: nft_contract(),
+            sealed_metadata: Some(true),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: vec![preload("1", "https://snails.io/1.gif")],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let handle_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        );
+        let HandleResponse { messages, .. } = handle_result.unwrap();
+        let mint = extract_mints(&messages[0]).pop().unwrap();
+
+        // the image is sealed in the private metadata, the race traits are only public
+        let public = mint.public_metadata.clone().unwrap().extension.unwrap();
+        assert_eq!(public.media, None);
+        assert_eq!(
+            public.attributes,
+            Some(vec![stat("Wins", 0), stat("Loses", 0)])
+        );
+        let private = mint.private_metadata.clone().unwrap().extension.unwrap();
+        assert_eq!(private.media.unwrap()[0].url, "https://snails.io/1.gif");
+        let private_traits: Vec<Option<String>> = private
+            .attributes
+            .unwrap()
+            .into_iter()
+            .map(|t| t.trait_type)
+            .collect();
+        assert_eq!(private_traits, vec![Some("Category".to_string())]);
+
+        // the snail races before its owner reveals it
+        let mut nft_deps = mock_dependencies(20, &[]);
+        let nft_init_msg = secret_snails_nft::msg::InitMsg {
+            name: "Secret Snails".to_string(),
+            symbol: "SNAIL".to_string(),
+            admin: Some(HumanAddr(MOCK_CONTRACT_ADDR.to_string())),
+            entropy: "snails".to_string(),
+            royalty_info: None,
+            config: Some(secret_snails_nft::msg::InitConfig {
+                enable_sealed_metadata: Some(true),
+                ..Default::default()
+            }),
+            post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
+        };
+        secret_snails_nft::contract::init(&mut nft_deps, mock_env("admin", &[]), nft_init_msg)
+            .unwrap();
+        let execute_on_nft = |nft_deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                              message: &CosmosMsg| match message {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                secret_snails_nft::contract::handle(
+                    nft_deps,
+                    mock_env(MOCK_CONTRACT_ADDR, &[]),
+                    from_binary(msg).unwrap(),
+                )
+                .unwrap();
+            }
+            _ => panic!("Expected a wasm execute message"),
+        };
+        execute_on_nft(&mut nft_deps, &messages[0]);
+
+        let handle_msg = HandleMsg::GrantRole {
+            role: Role::GameOperator,
+            address: HumanAddr("race".to_string()),
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let mut deps = with_nft_info_querier(deps, mint.public_metadata.unwrap());
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 1,
+            loses_delta: 2,
+        };
+        let HandleResponse { messages, .. } =
+            handle(&mut deps, mock_env("race", &[]), handle_msg).unwrap();
+        execute_on_nft(&mut nft_deps, &messages[0]);
+
+        // and keeps its stats once revealed
+        let handle_msg = secret_snails_nft::msg::HandleMsg::Reveal {
+            token_id: "1".to_string(),
+            padding: None,
+        };
+        secret_snails_nft::contract::handle(&mut nft_deps, mock_env("buyer", &[]), handle_msg)
+            .unwrap();
+        let query_msg = secret_snails_nft::msg::QueryMsg::NftInfo {
+            token_id: "1".to_string(),
+        };
+        let NftsQueryAnswer::NftInfo { extension, .. } =
+            from_binary(&secret_snails_nft::contract::query(&nft_deps, query_msg).unwrap())
+                .unwrap();
+        let revealed = extension.unwrap();
+        assert_eq!(revealed.media.unwrap()[0].url, "https://snails.io/1.gif");
+        let revealed_traits: Vec<(Option<String>, String)> = revealed
+            .attributes
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.trait_type, t.value))
+            .collect();
+        assert_eq!(
+            revealed_traits,
+            vec![
+                (Some("Category".to_string()), "Stephen Hawking".to_string()),
+                (Some("Wins".to_string()), "1".to_string()),
+                (Some("Loses".to_string()), "2".to_string())
+            ]
+        );
+    }
+
+    // Token pool indexing tests
+
+    #[test]
+    fn test_load_metadata_past_u16() {
+        let (init_result, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        init_result.unwrap();
+        // stands for a pool that already has u16::MAX tokens
+        save(&mut deps.storage, b"count", &(u16::MAX as u32)).unwrap();
+
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: vec![
+                preload("a", "https://snails.io/a.gif"),
+                preload("b", "https://snails.io/b.gif"),
+            ],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(provenance(&deps).1, u16::MAX as u32 + 2);
+        assert_eq!(mint_status(&deps).4, u16::MAX as u32 + 2);
+    }
+
+    #[test]
+    fn test_withdraw() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+
+        let handle_msg = HandleMsg::WithdrawToken {
+            token: token_contract(),
+            amount: Uint128(500),
+            recipient: HumanAddr("treasury".to_string()),
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a treasurer can execute this action!"));
+
+        let HandleResponse { messages, .. } =
+            handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let expected = transfer_msg(
+            HumanAddr("treasury".to_string()),
+            Uint128(500),
+            None,
+            None,
+            BLOCK_SIZE,
+            token_contract().token_code_hash,
+            token_contract().contract_addr,
+        )
+        .unwrap();
+        assert_eq!(messages, vec![expected]);
+
+        let handle_msg = HandleMsg::WithdrawNative {
+            denom: "uscrt".to_string(),
+            amount: Uint128(700),
+            recipient: HumanAddr("treasury".to_string()),
+        };
+        let HandleResponse { messages, .. } =
+            handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(
+            messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr(MOCK_CONTRACT_ADDR.to_string()),
+                to_address: HumanAddr("treasury".to_string()),
+                amount: coins(700, "uscrt"),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_update_revenue_split() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let new_split = vec![split("alice", 600_000), split("bob", 400_000)];
+
+        let handle_msg = HandleMsg::UpdateRevenueSplit {
+            revenue_split: new_split.clone(),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint should be stoped to perform this"));
+
+        let pause_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: false,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), pause_msg).unwrap();
+
+        let handle_result = handle(&mut deps, mock_env("bob", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+
+        let bad_split = HandleMsg::UpdateRevenueSplit {
+            revenue_split: vec![split("alice", 600_000)],
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), bad_split).is_err());
+
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let open_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: true,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        handle(&mut deps, mock_env("admin", &[]), open_msg).unwrap();
+        let HandleResponse { messages, .. } = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        )
+        .unwrap();
+        let bob_share = transfer_msg(
+            HumanAddr("bob".to_string()),
+            Uint128(400_000),
+            None,
+            None,
+            BLOCK_SIZE,
+            token_contract().token_code_hash,
+            token_contract().contract_addr,
+        )
+        .unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages.contains(&bob_share));
+    }
+
+    /// The SNIP-20 balance query the minter sends with its viewing key
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Snip20QueryMsg {
+        Balance { address: HumanAddr, key: String },
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Snip20QueryAnswer {
+        Balance { amount: Uint128 },
+    }
+
+    /// Answers the SNIP-20 balance of the minter when asked with `key`, and bank queries
+    struct BalanceQuerier {
+        bank: MockQuerier,
+        key: String,
+        amount: Uint128,
+    }
+
+    impl Querier for BalanceQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match request {
+                QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr, msg, ..
+                }) if contract_addr == token_contract().contract_addr => {
+                    let Snip20QueryMsg::Balance { address, key } = from_binary(&msg).unwrap();
+                    assert_eq!(address, HumanAddr(MOCK_CONTRACT_ADDR.to_string()));
+                    assert_eq!(key, self.key);
+                    Ok(to_binary(&Snip20QueryAnswer::Balance {
+                        amount: self.amount,
+                    }))
+                }
+                _ => self.bank.raw_query(bin_request),
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_balances() {
+        let deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let mut deps = Extern {
+            storage: deps.storage,
+            api: deps.api,
+            querier: BalanceQuerier {
+                bank: MockQuerier::new(&[(
+                    &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                    &coins(42, "uscrt"),
+                )]),
+                key: "minter_key".to_string(),
+                amount: Uint128(1_500),
+            },
+        };
+        let admin = HumanAddr("admin".to_string());
+
+        // no viewing key yet, only the native balance
+        let query_answer: QueryAnswer =
+            from_binary(&query_balances(&deps, &admin).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::Balances { tokens, native } => {
+                assert_eq!(tokens, vec![]);
+                assert_eq!(native, Some(coins(42, "uscrt")[0].clone()));
+            }
+            _ => panic!("Unexpected query answer"),
+        }
+
+        let handle_msg = HandleMsg::SetTokenViewingKey {
+            token: token_contract(),
+            key: "minter_key".to_string(),
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a treasurer can execute this action!"));
+        let HandleResponse { messages, .. } =
+            handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let expected = set_viewing_key_msg(
+            "minter_key".to_string(),
+            None,
+            BLOCK_SIZE,
+            token_contract().token_code_hash,
+            token_contract().contract_addr,
+        )
+        .unwrap();
+        assert_eq!(messages, vec![expected]);
+
+        let query_answer: QueryAnswer =
+            from_binary(&query_balances(&deps, &admin).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::Balances { tokens, .. } => assert_eq!(
+                tokens,
+                vec![TokenBalance {
+                    contract_addr: token_contract().contract_addr,
+                    amount: Uint128(1_500),
+                }]
+            ),
+            _ => panic!("Unexpected query answer"),
+        }
+
+        let query_result = query_balances(&deps, &HumanAddr("alice".to_string()));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only admin or a treasurer can execute this action!"));
+    }
+
+    fn admins<S: Storage, A: Api, Q: Querier>(
+        deps: &Extern<S, A, Q>,
+    ) -> (HumanAddr, Option<HumanAddr>) {
+        let query_answer: QueryAnswer =
+            from_binary(&query(deps, QueryMsg::Info {}).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::Info {
+                admin,
+                pending_admin,
+                ..
+            } => (admin, pending_admin),
+            _ => panic!("Unexpected query answer"),
+        }
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let (_, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        let admin = HumanAddr("admin".to_string());
+        let alice = HumanAddr("alice".to_string());
+
+        let propose_msg = HandleMsg::ProposeAdmin {
+            address: alice.clone(),
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), propose_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+
+        // nothing changes until the proposed admin accepts
+        handle(&mut deps, mock_env("admin", &[]), propose_msg.clone()).unwrap();
+        assert_eq!(admins(&deps), (admin.clone(), Some(alice.clone())));
+        let handle_result = handle(&mut deps, mock_env("bob", &[]), HandleMsg::AcceptAdmin {});
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only the proposed admin can accept the admin rights"));
+
+        // a cancelled proposal can't be accepted
+        handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::CancelAdminProposal {},
+        )
+        .unwrap();
+        assert_eq!(admins(&deps), (admin.clone(), None));
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), HandleMsg::AcceptAdmin {});
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only the proposed admin can accept the admin rights"));
+
+        handle(&mut deps, mock_env("admin", &[]), propose_msg).unwrap();
+        handle(&mut deps, mock_env("alice", &[]), HandleMsg::AcceptAdmin {}).unwrap();
+        assert_eq!(admins(&deps), (alice, None));
+        let handle_msg = HandleMsg::ProposeAdmin { address: admin };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+    }
+
+    fn roles<S: Storage, A: Api, Q: Querier>(
+        deps: &Extern<S, A, Q>,
+    ) -> Vec<(Role, Vec<HumanAddr>)> {
+        let query_answer: QueryAnswer =
+            from_binary(&query(deps, QueryMsg::Roles {}).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::Roles { roles } => roles
+                .into_iter()
+                .map(|holders| (holders.role, holders.addresses))
+                .collect(),
+            _ => panic!("Unexpected query answer"),
+        }
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let ops = HumanAddr("ops".to_string());
+        let pause_msg = HandleMsg::UpdateMint {
+            whitelist_mint_enabled: false,
+            standard_mint_enabled: false,
+            mint_price: None,
+            max_mint_per_tx: None,
+            commit_reveal_enabled: None,
+        };
+        let withdraw_msg = HandleMsg::WithdrawNative {
+            denom: "uscrt".to_string(),
+            amount: Uint128(700),
+            recipient: ops.clone(),
+        };
+
+        let handle_result = handle(&mut deps, mock_env("ops", &[]), pause_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+
+        let grant_msg = HandleMsg::GrantRole {
+            role: Role::MintOperator,
+            address: ops.clone(),
+        };
+        let handle_result = handle(&mut deps, mock_env("ops", &[]), grant_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+        let handle_msg = HandleMsg::GrantRole {
+            role: Role::Admin,
+            address: ops.clone(),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The admin role can only be transferred with ProposeAdmin"));
+
+        // the mint operator can pause the mint, but not withdraw or grant roles
+        handle(&mut deps, mock_env("admin", &[]), grant_msg.clone()).unwrap();
+        handle(&mut deps, mock_env("ops", &[]), pause_msg.clone()).unwrap();
+        let handle_result = handle(&mut deps, mock_env("ops", &[]), withdraw_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a treasurer can execute this action!"));
+        let handle_result = handle(&mut deps, mock_env("ops", &[]), grant_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+
+        let handle_msg = HandleMsg::UpdateChangeMetadataPermitedAdresses {
+            change_metadata_permited_addresses: vec![HumanAddr("race".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        assert_eq!(
+            roles(&deps),
+            vec![
+                (Role::Admin, vec![HumanAddr("admin".to_string())]),
+                (Role::MintOperator, vec![ops.clone()]),
+                (Role::MetadataLoader, vec![]),
+                (Role::Treasurer, vec![]),
+                (Role::GameOperator, vec![HumanAddr("race".to_string())]),
+            ]
+        );
+
+        // revoking a game operator set with UpdateChangeMetadataPermitedAdresses removes it
+        let revoke_msg = HandleMsg::RevokeRole {
+            role: Role::GameOperator,
+            address: HumanAddr("race".to_string()),
+        };
+        handle(&mut deps, mock_env("admin", &[]), revoke_msg.clone()).unwrap();
+        assert_eq!(roles(&deps)[4], (Role::GameOperator, vec![]));
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 1,
+            loses_delta: 0,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only game operators can execute this action!"));
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), revoke_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("race is not a game operator"));
+
+        let revoke_msg = HandleMsg::RevokeRole {
+            role: Role::MintOperator,
+            address: ops.clone(),
+        };
+        handle(&mut deps, mock_env("admin", &[]), revoke_msg.clone()).unwrap();
+        let handle_result = handle(&mut deps, mock_env("ops", &[]), pause_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), revoke_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("ops is not a mint operator"));
+
+        let handle_msg = HandleMsg::GrantRole {
+            role: Role::Treasurer,
+            address: ops,
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        handle(&mut deps, mock_env("ops", &[]), withdraw_msg).unwrap();
+    }
+
+    fn set_status(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        sender: &str,
+        status: ContractStatus,
+    ) -> StdResult<HandleResponse> {
+        handle(
+            deps,
+            mock_env(sender, &[]),
+            HandleMsg::SetContractStatus { status },
+        )
+    }
+
+    #[test]
+    fn test_contract_status() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let mint_paused = ContractStatus {
+            mint_paused: true,
+            ..ContractStatus::default()
+        };
+
+        let error = extract_error_msg(set_status(&mut deps, "ops", mint_paused.clone()));
+        assert!(error.contains("Only admin or a mint operator can execute this action!"));
+        set_status(&mut deps, "admin", mint_paused.clone()).unwrap();
+        let query_answer: QueryAnswer =
+            from_binary(&query(&deps, QueryMsg::ContractStatus {}).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::ContractStatus { status } => assert_eq!(status, mint_paused),
+            _ => panic!("Unexpected query answer"),
+        }
+
+        // the tokens sent to mint are refunded, native mints fail
+        let HandleResponse {
+            messages,
+            log: logs,
+            ..
+        } = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        )
+        .unwrap();
+        let refund = transfer_msg(
+            HumanAddr("buyer".to_string()),
+            Uint128(1_000_000),
+            None,
+            None,
+            BLOCK_SIZE,
+            token_contract().token_code_hash,
+            token_contract().contract_addr,
+        )
+        .unwrap();
+        assert_eq!(messages, vec![refund.clone()]);
+        assert_eq!(logs, vec![log("refund", 1_000_000)]);
+        assert_eq!(mint_status(&deps).4, 10);
+        let handle_msg = HandleMsg::MintNfts {
+            count: 1,
+            entropy: None,
+            whitelist_proof: None,
+            partial_fill: None,
+        };
+        let handle_result = handle(
+            &mut deps,
+            mock_env("buyer", &coins(1_000_000, "uscrt")),
+            handle_msg,
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The contract admin has temporarily disabled this action"));
+
+        // game updates are paused on their own
+        let handle_msg = HandleMsg::UpdateChangeMetadataPermitedAdresses {
+            change_metadata_permited_addresses: vec![HumanAddr("race".to_string())],
+        };
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+        let game_paused = ContractStatus {
+            game_paused: true,
+            ..ContractStatus::default()
+        };
+        set_status(&mut deps, "admin", game_paused).unwrap();
+        let handle_msg = HandleMsg::UpdateSnailStats {
+            token_id: "1".to_string(),
+            wins_delta: 1,
+            loses_delta: 0,
+        };
+        let handle_result = handle(&mut deps, mock_env("race", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The contract admin has temporarily disabled this action"));
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        )
+        .unwrap();
+        assert_eq!(mint_status(&deps).4, 9);
+
+        // stopping everything leaves only SetContractStatus, and refunds
+        let stop_all = ContractStatus {
+            stop_all: true,
+            ..ContractStatus::default()
+        };
+        set_status(&mut deps, "admin", stop_all).unwrap();
+        let handle_msg = HandleMsg::SetMaxMintPerWallet {
+            max_mint_per_wallet: Some(1),
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The contract admin has temporarily disabled this action"));
+        let HandleResponse { messages, .. } = handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        )
+        .unwrap();
+        assert_eq!(messages, vec![refund]);
+
+        set_status(&mut deps, "admin", ContractStatus::default()).unwrap();
+        handle(
+            &mut deps,
+            mock_env("sscrt", &[]),
+            receive_msg("buyer", 1_000_000, 1),
+        )
+        .unwrap();
+        assert_eq!(mint_status(&deps).4, 8);
+    }
+
+    #[test]
+    fn test_mint_paused_counts_as_stopped_for_the_split() {
+        let mut deps = mint_ready_helper(vec![split("alice", 1_000_000)], 10);
+        let split_msg = HandleMsg::UpdateRevenueSplit {
+            revenue_split: vec![split("alice", 600_000), split("bob", 400_000)],
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), split_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint should be stoped to perform this"));
+
+        // the mint flags are still on, but nobody can mint while the status pauses it
+        let mint_paused = ContractStatus {
+            mint_paused: true,
+            ..ContractStatus::default()
+        };
+        set_status(&mut deps, "admin", mint_paused).unwrap();
+        handle(&mut deps, mock_env("admin", &[]), split_msg).unwrap();
+
+        // the NFT contract still needs the mint flags off, a pause doesn't settle pending mints
+        let handle_msg = HandleMsg::AddNftContract {
+            contract: SecretContract {
+                contract_addr: HumanAddr("snails2".to_string()),
+                token_code_hash: "snails2_hash".to_string(),
+            },
+            sealed_metadata: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Mint should be stoped to perform this"));
+        assert_eq!(
+            mint_status(&deps).0.unwrap().contract_addr,
+            nft_contract().contract_addr
+        );
+    }
+
+    #[test]
+    fn test_metadata_paused() {
+        let (_, mut deps) = init_helper(vec![split("alice", 1_000_000)]);
+        let metadata_paused = ContractStatus {
+            metadata_paused: true,
+            ..ContractStatus::default()
+        };
+        set_status(&mut deps, "admin", metadata_paused).unwrap();
+
+        let handle_msg = HandleMsg::LoadMetadata {
+            new_data: vec![preload("1", "https://snails.io/1.gif")],
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("The contract admin has temporarily disabled this action"));
+
+        set_status(&mut deps, "admin", ContractStatus::default()).unwrap();
+        handle(&mut deps, mock_env("admin", &[]), handle_msg).unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{handle, init};
+    use crate::msg::{HandleMsg, HandleReceiveMsg, InitMsg, NftsHandleMsg, PreLoad, RevenueSplit};
+    use crate::state::{ContractStatus, SecretContract};
+    use cosmwasm_std::testing::*;
+    use cosmwasm_std::{
+        from_binary, to_binary, CosmosMsg, Extern, HandleResponse, HumanAddr, StdError, StdResult,
+        Uint128, WasmMsg,
+    };
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    const TOKEN: &str = "sscrt";
+    const NFT: &str = "snails";
+    const PRICE: u128 = 1_000_000;
+
+    /// The SNIP-20 messages the minter sends to the token contract
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Snip20HandleMsg {
+        Transfer {
+            recipient: HumanAddr,
+            amount: Uint128,
+        },
+    }
+
+    /// Drives the minter the way the chain does.  Buyers hold balances on a mocked SNIP-20 that
+    /// moves the tokens to the minter and calls its Receive when they Send them, and the
+    /// messages the minter answers with are executed on the balances and the minted NFTs.
+    /// A failed handle only rolls the balances back, the minter has to do all of its checks
+    /// before it writes anything
+    struct MintFlow {
+        deps: Extern<MockStorage, MockApi, MockQuerier>,
+        balances: HashMap<HumanAddr, u128>,
+        nft_owners: HashMap<String, HumanAddr>,
+        time: u64,
+    }
+
+    impl MintFlow {
+        fn new(
+            token_count: u16,
+            whitelist: Vec<(&str, u16)>,
+            max_mint_per_wallet: Option<u16>,
+            balances: &[(&str, u128)],
+        ) -> Self {
+            let mut deps = mock_dependencies(20, &[]);
+            let init_msg = InitMsg {
+                admin: Some(HumanAddr("admin".to_string())),
+                token_contract: SecretContract {
+                    contract_addr: HumanAddr(TOKEN.to_string()),
+                    token_code_hash: "sscrt_hash".to_string(),
+                },
+                entropy: "We're going to need a bigger boat".to_string(),
+                mint_price: Uint128(PRICE),
+                max_mint_per_tx: 5,
+                whitelist: whitelist
+                    .into_iter()
+                    .map(|(address, allowance)| (HumanAddr(address.to_string()), allowance))
+                    .collect(),
+                whitelist_merkle_root: None,
+                native_denom: None,
+                max_mint_per_wallet,
+                revenue_split: vec![RevenueSplit {
+                    address: HumanAddr("treasury".to_string()),
+                    percentage: 1_000_000,
+                }],
+                nft_contract_init: None,
+            };
+            init(&mut deps, mock_env("instantiator", &[]), init_msg).unwrap();
+
+            let mut flow = MintFlow {
+                deps,
+                balances: balances
+                    .iter()
+                    .map(|(address, balance)| (HumanAddr(address.to_string()), *balance))
+                    .collect(),
+                nft_owners: HashMap::new(),
+                time: mock_env("admin", &[]).block.time,
+            };
+
+            flow.admin(HandleMsg::AddNftContract {
+                contract: SecretContract {
+                    contract_addr: HumanAddr(NFT.to_string()),
+                    token_code_hash: "snails_hash".to_string(),
+                },
+                sealed_metadata: None,
+            });
+            flow.admin(HandleMsg::LoadMetadata {
+                new_data: (1..=token_count)
+                    .map(|id| PreLoad {
+                        id: id.to_string(),
+                        img_url: format!("https://snails.io/{}.gif", id),
+                        attributes: None,
+                        priv_attributes: None,
+                        hidden_attributes: None,
+                    })
+                    .collect(),
+            });
+
+            flow
+        }
+
+        fn admin(&mut self, msg: HandleMsg) {
+            let mut env = mock_env("admin", &[]);
+            env.block.time = self.time;
+            let response = handle(&mut self.deps, env, msg).unwrap();
+            self.execute(response.messages);
+        }
+
+        fn open_mint(&mut self, whitelist_mint_enabled: bool, standard_mint_enabled: bool) {
+            self.admin(HandleMsg::UpdateMint {
+                whitelist_mint_enabled,
+                standard_mint_enabled,
+                mint_price: None,
+                max_mint_per_tx: None,
+                commit_reveal_enabled: None,
+            });
+        }
+
+        /// `owner` sends `amount` tokens to the minter to mint `count` NFTs
+        fn mint(
+            &mut self,
+            owner: &str,
+            amount: u128,
+            count: u16,
+            partial_fill: bool,
+        ) -> StdResult<HandleResponse> {
+            self.mint_through(owner, owner, amount, count, partial_fill)
+        }
+
+        /// `sender` sends `amount` tokens of `owner` to the minter, like a router with an
+        /// allowance would
+        fn mint_through(
+            &mut self,
+            sender: &str,
+            owner: &str,
+            amount: u128,
+            count: u16,
+            partial_fill: bool,
+        ) -> StdResult<HandleResponse> {
+            let owner = HumanAddr(owner.to_string());
+            let minter = HumanAddr(MOCK_CONTRACT_ADDR.to_string());
+            let balances = self.balances.clone();
+
+            let balance = self.balance(&owner.0);
+            if balance < amount {
+                return Err(StdError::generic_err("insufficient funds"));
+            }
+            self.balances.insert(owner.clone(), balance - amount);
+            *self.balances.entry(minter).or_insert(0) += amount;
+
+            let msg = HandleMsg::Receive {
+                sender: HumanAddr(sender.to_string()),
+                from: owner,
+                amount: Uint128(amount),
+                msg: to_binary(&HandleReceiveMsg::MintNfts {
+                    count,
+                    entropy: None,
+                    whitelist_proof: None,
+                    partial_fill: Some(partial_fill),
+                })?,
+            };
+            let mut env = mock_env(TOKEN, &[]);
+            env.block.time = self.time;
+            match handle(&mut self.deps, env, msg) {
+                Ok(response) => {
+                    self.execute(response.messages.clone());
+                    Ok(response)
+                }
+                Err(error) => {
+                    self.balances = balances;
+                    Err(error)
+                }
+            }
+        }
+
+        fn execute(&mut self, messages: Vec<CosmosMsg>) {
+            for message in messages {
+                let (contract_addr, msg) = match message {
+                    CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr, msg, ..
+                    }) => (contract_addr, msg),
+                    _ => panic!("unexpected message {:?}", message),
+                };
+
+                if contract_addr.0 == TOKEN {
+                    let Snip20HandleMsg::Transfer { recipient, amount } =
+                        from_binary(&msg).unwrap();
+                    let minter = HumanAddr(MOCK_CONTRACT_ADDR.to_string());
+                    let balance = self.balance(MOCK_CONTRACT_ADDR);
+                    assert!(balance >= amount.u128(), "the minter can't pay {}", amount);
+                    self.balances.insert(minter, balance - amount.u128());
+                    *self.balances.entry(recipient).or_insert(0) += amount.u128();
+                } else if contract_addr.0 == NFT {
+                    match from_binary(&msg).unwrap() {
+                        NftsHandleMsg::BatchMintNft { mints, .. } => {
+                            for mint in mints {
+                                let token_id = mint.token_id.unwrap();
+                                let previous = self
+                                    .nft_owners
+                                    .insert(token_id.clone(), mint.owner.unwrap());
+                                assert_eq!(previous, None, "token {} minted twice", token_id);
+                            }
+                        }
+                        _ => panic!("unexpected NFT message"),
+                    }
+                } else {
+                    panic!("unexpected contract {}", contract_addr);
+                }
+            }
+        }
+
+        fn balance(&self, address: &str) -> u128 {
+            *self
+                .balances
+                .get(&HumanAddr(address.to_string()))
+                .unwrap_or(&0)
+        }
+
+        fn owned(&self, address: &str) -> usize {
+            self.nft_owners
+                .values()
+                .filter(|owner| owner.0 == address)
+                .count()
+        }
+    }
+
+    fn extract_error_msg<T>(error: StdResult<T>) -> String {
+        match error {
+            Ok(_) => panic!("Expected an error"),
+            Err(StdError::GenericErr { msg, .. }) => msg,
+            Err(error) => panic!("Unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_whitelist_mint_flow() {
+        let mut flow = MintFlow::new(
+            10,
+            vec![("alice", 2)],
+            None,
+            &[("alice", 5 * PRICE), ("bob", 5 * PRICE)],
+        );
+        flow.open_mint(true, false);
+
+        flow.mint("alice", 2 * PRICE, 2, false).unwrap();
+        assert_eq!(flow.owned("alice"), 2);
+        assert_eq!(flow.balance("alice"), 3 * PRICE);
+        assert_eq!(flow.balance("treasury"), 2 * PRICE);
+
+        let error = extract_error_msg(flow.mint("alice", PRICE, 1, false));
+        assert!(error.contains("address can only mint 0 more tokens thought the whitelist"));
+        let error = extract_error_msg(flow.mint("bob", PRICE, 1, false));
+        assert!(error.contains("buyer address not eligible for minting"));
+        assert_eq!(flow.balance("alice"), 3 * PRICE);
+        assert_eq!(flow.balance("bob"), 5 * PRICE);
+        assert_eq!(flow.balance(MOCK_CONTRACT_ADDR), 0);
+
+        // the public mint lets everybody in
+        flow.open_mint(false, true);
+        flow.mint("bob", PRICE, 1, false).unwrap();
+        assert_eq!(flow.owned("bob"), 1);
+        assert_eq!(flow.balance("treasury"), 3 * PRICE);
+    }
+
+    #[test]
+    fn test_mint_through_router_goes_to_owner() {
+        let mut flow = MintFlow::new(10, vec![("alice", 1)], None, &[("alice", 5 * PRICE)]);
+        flow.open_mint(true, false);
+
+        // the router isn't whitelisted, the owner of the tokens is the buyer
+        flow.mint_through("router", "alice", PRICE, 1, false)
+            .unwrap();
+        assert_eq!(flow.owned("alice"), 1);
+        assert_eq!(flow.owned("router"), 0);
+        let error = extract_error_msg(flow.mint_through("router", "alice", PRICE, 1, false));
+        assert!(error.contains("address can only mint 0 more tokens thought the whitelist"));
+    }
+
+    #[test]
+    fn test_wallet_cap_refund_flow() {
+        let mut flow = MintFlow::new(10, vec![], Some(2), &[("alice", 5 * PRICE)]);
+        flow.open_mint(false, true);
+
+        // only 2 of the 3 tokens fit in the wallet cap, the third one is refunded
+        let response = flow.mint("alice", 3 * PRICE, 3, true).unwrap();
+        assert_eq!(response.messages.len(), 3);
+        assert_eq!(flow.owned("alice"), 2);
+        assert_eq!(flow.balance("alice"), 3 * PRICE);
+        assert_eq!(flow.balance("treasury"), 2 * PRICE);
+        assert_eq!(flow.balance(MOCK_CONTRACT_ADDR), 0);
+
+        let error = extract_error_msg(flow.mint("alice", PRICE, 1, true));
+        assert!(error.contains("Mint limit per wallet reached, 0 left for this address"));
+        assert_eq!(flow.balance("alice"), 3 * PRICE);
+    }
+
+    #[test]
+    fn test_paused_mint_refund_flow() {
+        let mut flow = MintFlow::new(10, vec![], None, &[("alice", 5 * PRICE)]);
+        flow.open_mint(false, true);
+        flow.admin(HandleMsg::SetContractStatus {
+            status: ContractStatus {
+                mint_paused: true,
+                ..ContractStatus::default()
+            },
+        });
+
+        // the tokens sent while the mint is paused come back
+        flow.mint("alice", 2 * PRICE, 2, false).unwrap();
+        assert_eq!(flow.owned("alice"), 0);
+        assert_eq!(flow.balance("alice"), 5 * PRICE);
+        assert_eq!(flow.balance(MOCK_CONTRACT_ADDR), 0);
+
+        flow.admin(HandleMsg::SetContractStatus {
+            status: ContractStatus::default(),
+        });
+        flow.mint("alice", 2 * PRICE, 2, false).unwrap();
+        assert_eq!(flow.owned("alice"), 2);
+        assert_eq!(flow.balance("treasury"), 2 * PRICE);
+    }
+}
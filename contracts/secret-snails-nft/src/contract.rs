@@ -18,9 +18,10 @@ use crate::expiration::Expiration;
 use crate::inventory::{Inventory, InventoryIter};
 use crate::mint_run::{SerialNumber, StoredMintRunInfo};
 use crate::msg::{
-    AccessLevel, BatchNftDossierElement, Burn, ContractStatus, Cw721Approval, Cw721OwnerOfResponse,
-    HandleAnswer, HandleMsg, InitMsg, Mint, QueryAnswer, QueryMsg, QueryWithPermit, ReceiverInfo,
-    ResponseStatus::Success, Send, Snip721Approval, Transfer, ViewerInfo,
+    AccessLevel, BatchHiddenAttributesElement, BatchNftDossierElement, Burn, ContractStatus,
+    Cw721Approval, Cw721OwnerOfResponse, HandleAnswer, HandleMsg, InitMsg, Mint, QueryAnswer,
+    QueryMsg, QueryWithPermit, ReceiverInfo, ResponseStatus::Success, Send, Snip721Approval,
+    Transfer, ViewerInfo,
 };
 use crate::rand::sha_256;
 use crate::receiver::{batch_receive_nft_msg, receive_nft_msg};
@@ -28,12 +29,13 @@ use crate::royalties::{RoyaltyInfo, StoredRoyaltyInfo};
 use crate::state::{
     get_txs, json_may_load, json_save, load, may_load, remove, save, store_burn, store_mint,
     store_transfer, AuthList, Config, Permission, PermissionType, ReceiveRegistration, BLOCK_KEY,
-    CONFIG_KEY, CREATOR_KEY, DEFAULT_ROYALTY_KEY, MINTERS_KEY, MY_ADDRESS_KEY,
-    PREFIX_ALL_PERMISSIONS, PREFIX_AUTHLIST, PREFIX_INFOS, PREFIX_MAP_TO_ID, PREFIX_MAP_TO_INDEX,
-    PREFIX_MINT_RUN, PREFIX_MINT_RUN_NUM, PREFIX_OWNER_PRIV, PREFIX_PRIV_META, PREFIX_PUB_META,
-    PREFIX_RECEIVERS, PREFIX_REVOKED_PERMITS, PREFIX_ROYALTY_INFO, PREFIX_VIEW_KEY, PRNG_SEED_KEY,
+    CONFIG_KEY, CREATOR_KEY, DEFAULT_ROYALTY_KEY, HIDDEN_VIEWERS_KEY, MINTERS_KEY, MY_ADDRESS_KEY,
+    PENDING_ADMIN_KEY, PREFIX_ALL_PERMISSIONS, PREFIX_AUTHLIST, PREFIX_HIDDEN_ATTRIBUTES,
+    PREFIX_INFOS, PREFIX_MAP_TO_ID, PREFIX_MAP_TO_INDEX, PREFIX_MINT_RUN, PREFIX_MINT_RUN_NUM,
+    PREFIX_OWNER_PRIV, PREFIX_PRIV_META, PREFIX_PUB_META, PREFIX_RECEIVERS, PREFIX_REVOKED_PERMITS,
+    PREFIX_ROYALTY_INFO, PREFIX_VIEW_KEY, PRNG_SEED_KEY,
 };
-use crate::token::{Metadata, Token};
+use crate::token::{HiddenAttribute, Metadata, Token};
 use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
 
 /// pad handle responses and log attributes to blocks of 256 bytes to prevent leaking info based on
@@ -89,9 +91,24 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         burn_is_enabled: init_config.enable_burn.unwrap_or(false),
     };
 
-    let minters = vec![admin_raw];
+    let mut minters = vec![admin_raw];
+    for minter in msg.minters.unwrap_or_default().iter() {
+        let minter_raw = deps.api.canonical_address(minter)?;
+        if !minters.contains(&minter_raw) {
+            minters.push(minter_raw);
+        }
+    }
     save(&mut deps.storage, CONFIG_KEY, &config)?;
     save(&mut deps.storage, MINTERS_KEY, &minters)?;
+    if let Some(viewers) = msg.hidden_attribute_viewers {
+        let viewers_raw = viewers
+            .iter()
+            .map(|v| deps.api.canonical_address(v))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+        if !viewers_raw.is_empty() {
+            save(&mut deps.storage, HIDDEN_VIEWERS_KEY, &viewers_raw)?;
+        }
+    }
     save(&mut deps.storage, PRNG_SEED_KEY, &prng_seed)?;
     // TODO remove this after BlockInfo becomes available to queries
     save(&mut deps.storage, BLOCK_KEY, &env.block)?;
@@ -209,6 +226,18 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             public_metadata,
             private_metadata,
         ),
+        HandleMsg::SetHiddenAttributes {
+            token_id,
+            hidden_attributes,
+            ..
+        } => set_hidden_attributes(
+            deps,
+            env,
+            &config,
+            ContractStatus::StopTransactions.to_u8(),
+            &token_id,
+            hidden_attributes,
+        ),
         HandleMsg::SetRoyaltyInfo {
             token_id,
             royalty_info,
@@ -431,13 +460,43 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             ContractStatus::StopTransactions.to_u8(),
             &minters,
         ),
-        HandleMsg::ChangeAdmin { address, .. } => change_admin(
+        HandleMsg::AddHiddenAttributeViewers { viewers, .. } => add_hidden_viewers(
             deps,
             env,
-            &mut config,
+            &config,
+            ContractStatus::StopTransactions.to_u8(),
+            &viewers,
+        ),
+        HandleMsg::RemoveHiddenAttributeViewers { viewers, .. } => remove_hidden_viewers(
+            deps,
+            env,
+            &config,
+            ContractStatus::StopTransactions.to_u8(),
+            &viewers,
+        ),
+        HandleMsg::SetHiddenAttributeViewers { viewers, .. } => set_hidden_viewers(
+            deps,
+            env,
+            &config,
+            ContractStatus::StopTransactions.to_u8(),
+            &viewers,
+        ),
+        HandleMsg::ProposeAdmin { address, .. } => propose_admin(
+            deps,
+            env,
+            &config,
             ContractStatus::StopTransactions.to_u8(),
             &address,
         ),
+        HandleMsg::AcceptAdmin { .. } => accept_admin(
+            deps,
+            env,
+            &mut config,
+            ContractStatus::StopTransactions.to_u8(),
+        ),
+        HandleMsg::CancelAdminProposal { .. } => {
+            cancel_admin_proposal(deps, env, &config, ContractStatus::StopTransactions.to_u8())
+        }
         HandleMsg::SetContractStatus { level, .. } => {
             set_contract_status(deps, env, &mut config, level)
         }
@@ -499,6 +558,7 @@ pub fn mint<S: Storage, A: Api, Q: Querier>(
         royalty_info,
         transferable,
         memo,
+        hidden_attributes: None,
     }];
     let mut minted = mint_list(deps, &env, config, &sender_raw, mints)?;
     let minted_str = minted.pop().unwrap_or_else(String::new);
@@ -622,6 +682,7 @@ pub fn mint_clones<S: Storage, A: Api, Q: Querier>(
             royalty_info: royalty_info.clone(),
             transferable: Some(true),
             memo: memo.clone(),
+            hidden_attributes: None,
         });
         serial_number.serial_number += 1;
     }
@@ -701,6 +762,50 @@ pub fn set_metadata<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns HandleResult
+///
+/// sets new hidden attributes
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `priority` - u8 representation of highest status level this action is permitted at
+/// * `token_id` - token id String slice of token whose hidden attributes should be updated
+/// * `hidden_attributes` - the new hidden attributes
+pub fn set_hidden_attributes<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    priority: u8,
+    token_id: &str,
+    hidden_attributes: Vec<HiddenAttribute>,
+) -> HandleResult {
+    check_status(config.status, priority)?;
+    let custom_err = format!(
+        "Not authorized to update hidden attributes of token {}",
+        token_id
+    );
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let minters: Vec<CanonicalAddr> =
+        may_load(&deps.storage, MINTERS_KEY)?.unwrap_or_else(Vec::new);
+    if !minters.contains(&sender_raw) {
+        return Err(StdError::generic_err(custom_err));
+    }
+    // minters are allowed to know if a token exists
+    let (_, idx) = get_token(&deps.storage, token_id, None)?;
+    let mut hidden_store = PrefixedStorage::new(PREFIX_HIDDEN_ATTRIBUTES, &mut deps.storage);
+    save(&mut hidden_store, &idx.to_le_bytes(), &hidden_attributes)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetHiddenAttributes {
+            status: Success,
+        })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// sets new royalty information for a specified token or if no token ID is provided, sets new
@@ -834,9 +939,11 @@ pub fn reveal<S: Storage, A: Api, Q: Querier>(
     if !config.unwrap_to_private {
         let mut priv_store = PrefixedStorage::new(PREFIX_PRIV_META, &mut deps.storage);
         let may_priv: Option<Metadata> = may_load(&priv_store, &token_key)?;
-        if let Some(metadata) = may_priv {
+        if let Some(mut metadata) = may_priv {
             remove(&mut priv_store, &token_key);
             let mut pub_store = PrefixedStorage::new(PREFIX_PUB_META, &mut deps.storage);
+            let may_pub: Option<Metadata> = may_load(&pub_store, &token_key)?;
+            keep_public_attributes(&mut metadata, may_pub);
             save(&mut pub_store, &token_key, &metadata)?;
         }
     }
@@ -847,6 +954,34 @@ pub fn reveal<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Adds the attributes of the public metadata that the unwrapped metadata does not define, so
+/// the ones set while the token was sealed (like game stats) survive the reveal
+///
+/// # Arguments
+///
+/// * `unwrapped` - a mutable reference to the formerly sealed metadata
+/// * `public` - the public metadata the token had while it was sealed
+fn keep_public_attributes(unwrapped: &mut Metadata, public: Option<Metadata>) {
+    let public_attributes = public
+        .and_then(|m| m.extension)
+        .and_then(|e| e.attributes)
+        .unwrap_or_default();
+    if public_attributes.is_empty() {
+        return;
+    }
+    if let Some(extension) = unwrapped.extension.as_mut() {
+        let attributes = extension.attributes.get_or_insert_with(Vec::new);
+        for attribute in public_attributes {
+            if !attributes
+                .iter()
+                .any(|a| a.trait_type == attribute.trait_type)
+            {
+                attributes.push(attribute);
+            }
+        }
+    }
+}
+
 /// Returns HandleResult
 ///
 /// grants/revokes trasfer permission on a token
@@ -1623,19 +1758,168 @@ pub fn set_minters<S: Storage, A: Api, Q: Querier>(
 
 /// Returns HandleResult
 ///
-/// change the admin address
+/// add a list of hidden attribute viewers
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
 /// * `env` - Env of contract's environment
-/// * `config` - a mutable reference to the Config
+/// * `config` - a reference to the Config
 /// * `priority` - u8 representation of highest ContractStatus level this action is permitted
-/// * `address` - new admin address
-pub fn change_admin<S: Storage, A: Api, Q: Querier>(
+/// * `new_viewers` - list of viewer addresses to add
+pub fn add_hidden_viewers<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    config: &mut Config,
+    config: &Config,
+    priority: u8,
+    new_viewers: &[HumanAddr],
+) -> HandleResult {
+    check_status(config.status, priority)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.admin != sender_raw {
+        return Err(StdError::generic_err(
+            "This is an admin command and can only be run from the admin address",
+        ));
+    }
+    let mut viewers: Vec<CanonicalAddr> =
+        may_load(&deps.storage, HIDDEN_VIEWERS_KEY)?.unwrap_or_else(Vec::new);
+    let old_len = viewers.len();
+    for viewer in new_viewers {
+        let viewer_raw = deps.api.canonical_address(viewer)?;
+        if !viewers.contains(&viewer_raw) {
+            viewers.push(viewer_raw);
+        }
+    }
+    // only save if the list changed
+    if old_len != viewers.len() {
+        save(&mut deps.storage, HIDDEN_VIEWERS_KEY, &viewers)?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AddHiddenAttributeViewers {
+            status: Success,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// remove a list of hidden attribute viewers
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `priority` - u8 representation of highest ContractStatus level this action is permitted
+/// * `no_viewers` - list of viewer addresses to remove
+pub fn remove_hidden_viewers<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    priority: u8,
+    no_viewers: &[HumanAddr],
+) -> HandleResult {
+    check_status(config.status, priority)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.admin != sender_raw {
+        return Err(StdError::generic_err(
+            "This is an admin command and can only be run from the admin address",
+        ));
+    }
+    let may_viewers: Option<Vec<CanonicalAddr>> = may_load(&deps.storage, HIDDEN_VIEWERS_KEY)?;
+    if let Some(mut viewers) = may_viewers {
+        let old_len = viewers.len();
+        let no_raw: Vec<CanonicalAddr> = no_viewers
+            .iter()
+            .map(|x| deps.api.canonical_address(x))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+        viewers.retain(|v| !no_raw.contains(v));
+        let new_len = viewers.len();
+        if new_len > 0 {
+            if old_len != new_len {
+                save(&mut deps.storage, HIDDEN_VIEWERS_KEY, &viewers)?;
+            }
+        } else {
+            remove(&mut deps.storage, HIDDEN_VIEWERS_KEY);
+        }
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveHiddenAttributeViewers {
+            status: Success,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// define the exact list of hidden attribute viewers
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `priority` - u8 representation of highest ContractStatus level this action is permitted
+/// * `human_viewers` - exact list of viewer addresses
+pub fn set_hidden_viewers<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    priority: u8,
+    human_viewers: &[HumanAddr],
+) -> HandleResult {
+    check_status(config.status, priority)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.admin != sender_raw {
+        return Err(StdError::generic_err(
+            "This is an admin command and can only be run from the admin address",
+        ));
+    }
+    // remove duplicates from the viewers list
+    let viewers_raw: Vec<CanonicalAddr> = human_viewers
+        .iter()
+        .map(|x| deps.api.canonical_address(x))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    let mut sortable: Vec<&[u8]> = viewers_raw.iter().map(|x| x.as_slice()).collect();
+    sortable.sort_unstable();
+    sortable.dedup();
+    let viewers: Vec<CanonicalAddr> = sortable
+        .iter()
+        .map(|x| CanonicalAddr(Binary(x.to_vec())))
+        .collect();
+    if viewers.is_empty() {
+        remove(&mut deps.storage, HIDDEN_VIEWERS_KEY);
+    } else {
+        save(&mut deps.storage, HIDDEN_VIEWERS_KEY, &viewers)?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetHiddenAttributeViewers {
+            status: Success,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// propose a new admin address, which has to accept before it gets admin authority
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `priority` - u8 representation of highest ContractStatus level this action is permitted
+/// * `address` - proposed admin address
+pub fn propose_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
     priority: u8,
     address: &HumanAddr,
 ) -> HandleResult {
@@ -1646,15 +1930,79 @@ pub fn change_admin<S: Storage, A: Api, Q: Querier>(
             "This is an admin command and can only be run from the admin address",
         ));
     }
-    let new_admin = deps.api.canonical_address(address)?;
-    if new_admin != config.admin {
-        config.admin = new_admin;
-        save(&mut deps.storage, CONFIG_KEY, &config)?;
+    let pending_admin = deps.api.canonical_address(address)?;
+    save(&mut deps.storage, PENDING_ADMIN_KEY, &pending_admin)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ProposeAdmin { status: Success })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// give admin authority to the message sender if it is the proposed admin
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `config` - a mutable reference to the Config
+/// * `priority` - u8 representation of highest ContractStatus level this action is permitted
+pub fn accept_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &mut Config,
+    priority: u8,
+) -> HandleResult {
+    check_status(config.status, priority)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let pending_admin: Option<CanonicalAddr> = may_load(&deps.storage, PENDING_ADMIN_KEY)?;
+    if pending_admin.as_ref() != Some(&sender_raw) {
+        return Err(StdError::generic_err(
+            "Only the proposed admin can accept the admin authority",
+        ));
     }
+    config.admin = sender_raw;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    remove(&mut deps.storage, PENDING_ADMIN_KEY);
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::ChangeAdmin { status: Success })?),
+        data: Some(to_binary(&HandleAnswer::AcceptAdmin { status: Success })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// withdraw the pending admin proposal
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `priority` - u8 representation of highest ContractStatus level this action is permitted
+pub fn cancel_admin_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    priority: u8,
+) -> HandleResult {
+    check_status(config.status, priority)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.admin != sender_raw {
+        return Err(StdError::generic_err(
+            "This is an admin command and can only be run from the admin address",
+        ));
+    }
+    remove(&mut deps.storage, PENDING_ADMIN_KEY);
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CancelAdminProposal {
+            status: Success,
+        })?),
     })
 }
 
@@ -1731,7 +2079,7 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
         QueryMsg::RoyaltyInfo { token_id, viewer } => {
             query_royalty(deps, token_id.as_deref(), viewer, None)
         }
-        QueryMsg::ContractConfig {} => query_config(&deps.storage),
+        QueryMsg::ContractConfig {} => query_config(deps),
         QueryMsg::Minters {} => query_minters(deps),
         QueryMsg::NumTokens { viewer } => query_num_tokens(deps, viewer, None),
         QueryMsg::AllTokens {
@@ -1825,6 +2173,13 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             query_transactions(deps, viewer, page, page_size, None)
         }
         QueryMsg::RegisteredCodeHash { contract } => query_code_hash(deps, &contract),
+        QueryMsg::HiddenAttributes { token_id, viewer } => {
+            query_hidden_attributes(deps, &token_id, Some(viewer), None)
+        }
+        QueryMsg::BatchHiddenAttributes { token_ids, viewer } => {
+            query_batch_hidden_attributes(deps, token_ids, Some(viewer), None)
+        }
+        QueryMsg::HiddenAttributeViewers {} => query_hidden_viewers(deps),
         QueryMsg::WithPermit { permit, query } => permit_queries(deps, permit, query),
     };
     pad_query_result(response, BLOCK_SIZE)
@@ -1867,6 +2222,9 @@ pub fn permit_queries<S: Storage, A: Api, Q: Querier>(
         QueryWithPermit::PrivateMetadata { token_id } => {
             query_private_meta(deps, &token_id, None, Some(querier))
         }
+        QueryWithPermit::HiddenAttributes { token_ids } => {
+            query_batch_hidden_attributes(deps, token_ids, None, Some(querier))
+        }
         QueryWithPermit::NftDossier {
             token_id,
             include_expired,
@@ -2022,9 +2380,10 @@ pub fn query_royalty<S: Storage, A: Api, Q: Querier>(
 ///
 /// # Arguments
 ///
-/// * `storage` - a reference to the contract's storage
-pub fn query_config<S: ReadonlyStorage>(storage: &S) -> QueryResult {
-    let config: Config = load(storage, CONFIG_KEY)?;
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+pub fn query_config<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let pending_admin: Option<CanonicalAddr> = may_load(&deps.storage, PENDING_ADMIN_KEY)?;
 
     to_binary(&QueryAnswer::ContractConfig {
         token_supply_is_public: config.token_supply_is_public,
@@ -2034,6 +2393,9 @@ pub fn query_config<S: ReadonlyStorage>(storage: &S) -> QueryResult {
         minter_may_update_metadata: config.minter_may_update_metadata,
         owner_may_update_metadata: config.owner_may_update_metadata,
         burn_is_enabled: config.burn_is_enabled,
+        pending_admin: pending_admin
+            .map(|a| deps.api.human_address(&a))
+            .transpose()?,
     })
 }
 
@@ -2185,6 +2547,75 @@ pub fn query_nft_info<S: ReadonlyStorage>(storage: &S, token_id: &str) -> QueryR
     })
 }
 
+/// Returns QueryResult displaying the hidden attributes of a token if the querier is a hidden
+/// attribute viewer
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `token_id` - string slice of the token id
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `from_permit` - address derived from an Owner permit, if applicable
+pub fn query_hidden_attributes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    token_id: &str,
+    viewer: Option<ViewerInfo>,
+    from_permit: Option<CanonicalAddr>,
+) -> QueryResult {
+    let viewer_raw = get_querier(deps, viewer, from_permit)?;
+    check_hidden_viewer(&deps.storage, viewer_raw.as_ref())?;
+    let hidden_attributes = load_hidden_attributes(&deps.storage, token_id)?;
+    to_binary(&QueryAnswer::HiddenAttributes { hidden_attributes })
+}
+
+/// Returns QueryResult displaying the hidden attributes of multiple tokens if the querier is a
+/// hidden attribute viewer
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `token_ids` - list of token ids whose hidden attributes should be displayed
+/// * `viewer` - optional address and key making an authenticated query request
+/// * `from_permit` - address derived from an Owner permit, if applicable
+pub fn query_batch_hidden_attributes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    token_ids: Vec<String>,
+    viewer: Option<ViewerInfo>,
+    from_permit: Option<CanonicalAddr>,
+) -> QueryResult {
+    let viewer_raw = get_querier(deps, viewer, from_permit)?;
+    check_hidden_viewer(&deps.storage, viewer_raw.as_ref())?;
+    let hidden_attributes = token_ids
+        .into_iter()
+        .map(|token_id| {
+            load_hidden_attributes(&deps.storage, &token_id).map(|hidden_attributes| {
+                BatchHiddenAttributesElement {
+                    token_id,
+                    hidden_attributes,
+                }
+            })
+        })
+        .collect::<StdResult<Vec<BatchHiddenAttributesElement>>>()?;
+    to_binary(&QueryAnswer::BatchHiddenAttributes { hidden_attributes })
+}
+
+/// Returns QueryResult displaying the list of hidden attribute viewers
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+pub fn query_hidden_viewers<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let viewers: Vec<CanonicalAddr> =
+        may_load(&deps.storage, HIDDEN_VIEWERS_KEY)?.unwrap_or_else(Vec::new);
+
+    to_binary(&QueryAnswer::HiddenAttributeViewers {
+        viewers: viewers
+            .iter()
+            .map(|v| deps.api.human_address(v))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+    })
+}
+
 /// Returns QueryResult displaying the private metadata of a token if permitted to
 /// view it
 ///
@@ -4535,6 +4966,10 @@ fn burn_list<S: Storage, A: Api, Q: Querier>(
             remove(&mut pub_store, &token_key);
             let mut priv_store = PrefixedStorage::new(PREFIX_PRIV_META, &mut deps.storage);
             remove(&mut priv_store, &token_key);
+            // remove hidden attributes if existent
+            let mut hidden_store =
+                PrefixedStorage::new(PREFIX_HIDDEN_ATTRIBUTES, &mut deps.storage);
+            remove(&mut hidden_store, &token_key);
             // remove mint run info if existent
             let mut run_store = PrefixedStorage::new(PREFIX_MINT_RUN, &mut deps.storage);
             remove(&mut run_store, &token_key);
@@ -4650,6 +5085,12 @@ fn mint_list<S: Storage, A: Api, Q: Querier>(
             let mut priv_store = PrefixedStorage::new(PREFIX_PRIV_META, &mut deps.storage);
             save(&mut priv_store, &token_key, &priv_meta)?;
         }
+        // save the hidden attributes
+        if let Some(hidden_attributes) = mint.hidden_attributes {
+            let mut hidden_store =
+                PrefixedStorage::new(PREFIX_HIDDEN_ATTRIBUTES, &mut deps.storage);
+            save(&mut hidden_store, &token_key, &hidden_attributes)?;
+        }
         // save the mint run info
         let (mint_run, serial_number, quantity_minted_this_run) =
             if let Some(ser) = mint.serial_number {
@@ -4754,6 +5195,48 @@ fn store_royalties<S: Storage, A: Api>(
     }
 }
 
+/// Returns StdResult<Vec<HiddenAttribute>>
+///
+/// loads the hidden attributes of a token, which are empty if none were ever set.  Only call
+/// this after verifying the querier is a hidden attribute viewer
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token_id` - string slice of the token id
+fn load_hidden_attributes<S: ReadonlyStorage>(
+    storage: &S,
+    token_id: &str,
+) -> StdResult<Vec<HiddenAttribute>> {
+    // hidden attribute viewers are allowed to know if a token exists
+    let (_, idx) = get_token(storage, token_id, None)?;
+    let hidden_store = ReadonlyPrefixedStorage::new(PREFIX_HIDDEN_ATTRIBUTES, storage);
+    Ok(may_load(&hidden_store, &idx.to_le_bytes())?.unwrap_or_else(Vec::new))
+}
+
+/// Returns StdResult<()>
+///
+/// makes sure the querier is one of the addresses allowed to view hidden attributes
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `querier` - an optional reference to the querying address
+fn check_hidden_viewer<S: ReadonlyStorage>(
+    storage: &S,
+    querier: Option<&CanonicalAddr>,
+) -> StdResult<()> {
+    let viewers: Vec<CanonicalAddr> =
+        may_load(storage, HIDDEN_VIEWERS_KEY)?.unwrap_or_else(Vec::new);
+    if querier.map(|q| viewers.contains(q)).unwrap_or(false) {
+        Ok(())
+    } else {
+        Err(StdError::generic_err(
+            "Only hidden attribute viewers are allowed to view hidden attributes",
+        ))
+    }
+}
+
 /// Returns StdResult<()>
 ///
 /// makes sure that Metadata does not have both `token_uri` and `extension`
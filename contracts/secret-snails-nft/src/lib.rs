@@ -10,6 +10,7 @@ pub mod royalties;
 pub mod state;
 pub mod token;
 mod unittest_handles;
+mod unittest_hidden_attributes;
 mod unittest_inventory;
 mod unittest_mint_run;
 mod unittest_non_transferable;
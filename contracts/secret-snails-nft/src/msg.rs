@@ -8,7 +8,7 @@ use secret_toolkit::permit::Permit;
 use crate::expiration::Expiration;
 use crate::mint_run::{MintRunInfo, SerialNumber};
 use crate::royalties::{DisplayRoyaltyInfo, RoyaltyInfo};
-use crate::token::{Extension, Metadata};
+use crate::token::{Extension, HiddenAttribute, Metadata};
 
 /// Instantiation message
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -31,6 +31,10 @@ pub struct InitMsg {
     /// contract that instantiated it, but it could be used to execute any
     /// contract
     pub post_init_callback: Option<PostInitCallback>,
+    /// optional list of addresses allowed to view the hidden attributes of the tokens
+    pub hidden_attribute_viewers: Option<Vec<HumanAddr>>,
+    /// optional list of addresses allowed to mint besides the admin
+    pub minters: Option<Vec<HumanAddr>>,
 }
 
 /// This type represents optional configuration values.
@@ -170,6 +174,15 @@ pub enum HandleMsg {
         /// optional message length padding
         padding: Option<String>,
     },
+    /// set the hidden attributes of a token.  Only callable by minters
+    SetHiddenAttributes {
+        /// id of the token whose hidden attributes should be updated
+        token_id: String,
+        /// the new hidden attributes
+        hidden_attributes: Vec<HiddenAttribute>,
+        /// optional message length padding
+        padding: Option<String>,
+    },
     /// set royalty information.  If no token ID is provided, this royalty info will become the default
     /// RoyaltyInfo for any new tokens minted on the contract.  If a token ID is provided, this can only
     /// be called by the token creator and only when the creator is the current owner.  Royalties can not
@@ -382,13 +395,45 @@ pub enum HandleMsg {
         /// optional message length padding
         padding: Option<String>,
     },
-    /// change address with administrative power
-    ChangeAdmin {
-        /// address with admin authority
+    /// add addresses allowed to view hidden attributes
+    AddHiddenAttributeViewers {
+        /// list of addresses that can now view hidden attributes
+        viewers: Vec<HumanAddr>,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// revoke the ability to view hidden attributes from addresses
+    RemoveHiddenAttributeViewers {
+        /// list of addresses no longer allowed to view hidden attributes
+        viewers: Vec<HumanAddr>,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// define list of addresses allowed to view hidden attributes
+    SetHiddenAttributeViewers {
+        /// list of addresses allowed to view hidden attributes
+        viewers: Vec<HumanAddr>,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// propose a new address with administrative power.  It only becomes the admin once it
+    /// accepts with AcceptAdmin
+    ProposeAdmin {
+        /// proposed address with admin authority
         address: HumanAddr,
         /// optional message length padding
         padding: Option<String>,
     },
+    /// accept the admin authority proposed to the message sender
+    AcceptAdmin {
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// withdraw the pending admin proposal
+    CancelAdminProposal {
+        /// optional message length padding
+        padding: Option<String>,
+    },
     /// set contract status level to determine which functions are allowed.  StopTransactions
     /// status prevent mints, burns, sends, and transfers, but allows all other functions
     SetContractStatus {
@@ -440,6 +485,8 @@ pub struct Mint {
     pub transferable: Option<bool>,
     /// optional memo for the tx
     pub memo: Option<String>,
+    /// optional attributes that can only be seen by the hidden attribute viewers
+    pub hidden_attributes: Option<Vec<HiddenAttribute>>,
 }
 
 /// token burn info used when doing a BatchBurnNft
@@ -503,6 +550,9 @@ pub enum HandleAnswer {
     SetMetadata {
         status: ResponseStatus,
     },
+    SetHiddenAttributes {
+        status: ResponseStatus,
+    },
     SetRoyaltyInfo {
         status: ResponseStatus,
     },
@@ -564,7 +614,22 @@ pub enum HandleAnswer {
     SetMinters {
         status: ResponseStatus,
     },
-    ChangeAdmin {
+    AddHiddenAttributeViewers {
+        status: ResponseStatus,
+    },
+    RemoveHiddenAttributeViewers {
+        status: ResponseStatus,
+    },
+    SetHiddenAttributeViewers {
+        status: ResponseStatus,
+    },
+    ProposeAdmin {
+        status: ResponseStatus,
+    },
+    AcceptAdmin {
+        status: ResponseStatus,
+    },
+    CancelAdminProposal {
         status: ResponseStatus,
     },
     SetContractStatus {
@@ -820,6 +885,22 @@ pub enum QueryMsg {
     },
     /// display the contract's creator
     ContractCreator {},
+    /// displays the hidden attributes of a token.  Only the hidden attribute viewers are
+    /// permitted to view them
+    HiddenAttributes {
+        token_id: String,
+        /// address and key requesting to view the hidden attributes
+        viewer: ViewerInfo,
+    },
+    /// displays the hidden attributes of multiple tokens.  Only the hidden attribute viewers
+    /// are permitted to view them
+    BatchHiddenAttributes {
+        token_ids: Vec<String>,
+        /// address and key requesting to view the hidden attributes
+        viewer: ViewerInfo,
+    },
+    /// display the list of addresses allowed to view hidden attributes
+    HiddenAttributeViewers {},
     /// perform queries by passing permits instead of viewing keys
     WithPermit {
         /// permit used to verify querier identity
@@ -860,6 +941,13 @@ pub struct Cw721OwnerOfResponse {
     pub approvals: Vec<Cw721Approval>,
 }
 
+/// the token id and hidden attributes of a single token response in a batch query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchHiddenAttributesElement {
+    pub token_id: String,
+    pub hidden_attributes: Vec<HiddenAttribute>,
+}
+
 /// the token id and nft dossier info of a single token response in a batch query
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BatchNftDossierElement {
@@ -897,6 +985,8 @@ pub enum QueryAnswer {
         minter_may_update_metadata: bool,
         owner_may_update_metadata: bool,
         burn_is_enabled: bool,
+        /// address proposed as the new admin, waiting for it to accept
+        pending_admin: Option<HumanAddr>,
     },
     Minters {
         minters: Vec<HumanAddr>,
@@ -987,6 +1077,15 @@ pub enum QueryAnswer {
     ContractCreator {
         creator: Option<HumanAddr>,
     },
+    HiddenAttributes {
+        hidden_attributes: Vec<HiddenAttribute>,
+    },
+    BatchHiddenAttributes {
+        hidden_attributes: Vec<BatchHiddenAttributesElement>,
+    },
+    HiddenAttributeViewers {
+        viewers: Vec<HumanAddr>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -1028,6 +1127,9 @@ pub enum QueryWithPermit {
     },
     /// displays the private metadata if permitted to view it
     PrivateMetadata { token_id: String },
+    /// displays the hidden attributes of the listed tokens.  Only the hidden attribute viewers
+    /// are permitted to view them
+    HiddenAttributes { token_ids: Vec<String> },
     /// displays all the information about a token that the viewer has permission to
     /// see.  This may include the owner, the public metadata, the private metadata, royalty
     /// information, mint run information, whether the token is unwrapped, whether the token is
@@ -19,12 +19,16 @@ pub const CONFIG_KEY: &[u8] = b"config";
 pub const BLOCK_KEY: &[u8] = b"blockinfo";
 /// storage key for minters
 pub const MINTERS_KEY: &[u8] = b"minters";
+/// storage key for the addresses allowed to view hidden attributes
+pub const HIDDEN_VIEWERS_KEY: &[u8] = b"hiddenviewers";
 /// storage key for this contract's address
 pub const MY_ADDRESS_KEY: &[u8] = b"myaddr";
 /// storage key for prng seed
 pub const PRNG_SEED_KEY: &[u8] = b"prngseed";
 /// storage key for the contract instantiator
 pub const CREATOR_KEY: &[u8] = b"creator";
+/// storage key for the address proposed as the new admin
+pub const PENDING_ADMIN_KEY: &[u8] = b"pendingadmin";
 /// storage key for the default RoyaltyInfo to use if none is supplied when minting
 pub const DEFAULT_ROYALTY_KEY: &[u8] = b"defaultroy";
 /// prefix for storage that maps ids to indices
@@ -37,6 +41,8 @@ pub const PREFIX_INFOS: &[u8] = b"infos";
 pub const PREFIX_PUB_META: &[u8] = b"publicmeta";
 /// prefix for the storage of private metadata
 pub const PREFIX_PRIV_META: &[u8] = b"privatemeta";
+/// prefix for the storage of hidden attributes
+pub const PREFIX_HIDDEN_ATTRIBUTES: &[u8] = b"hiddenattrs";
 /// prefix for the storage of royalty information
 pub const PREFIX_ROYALTY_INFO: &[u8] = b"royalty";
 /// prefix for the storage of mint run information
@@ -100,3 +100,13 @@ pub struct Authentication {
     /// username used in basic authentication
     pub user: Option<String>,
 }
+
+/// attribute that can not be seen by anyone (not even the owner) except the hidden attribute
+/// viewers
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+pub struct HiddenAttribute {
+    /// name of the attribute
+    pub name: String,
+    /// attribute value
+    pub value: String,
+}
@@ -10,9 +10,9 @@ mod tests {
     use crate::receiver::Snip721ReceiveMsg;
     use crate::state::{
         get_txs, json_load, json_may_load, load, may_load, AuthList, Config, Permission,
-        PermissionType, CONFIG_KEY, MINTERS_KEY, PREFIX_ALL_PERMISSIONS, PREFIX_AUTHLIST,
-        PREFIX_INFOS, PREFIX_MAP_TO_ID, PREFIX_MAP_TO_INDEX, PREFIX_OWNER_PRIV, PREFIX_PRIV_META,
-        PREFIX_PUB_META, PREFIX_RECEIVERS, PREFIX_VIEW_KEY,
+        PermissionType, CONFIG_KEY, MINTERS_KEY, PENDING_ADMIN_KEY, PREFIX_ALL_PERMISSIONS,
+        PREFIX_AUTHLIST, PREFIX_INFOS, PREFIX_MAP_TO_ID, PREFIX_MAP_TO_INDEX, PREFIX_OWNER_PRIV,
+        PREFIX_PRIV_META, PREFIX_PUB_META, PREFIX_RECEIVERS, PREFIX_VIEW_KEY,
     };
     use crate::token::{Extension, Metadata, Token};
     use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
@@ -43,6 +43,8 @@ mod tests {
             royalty_info: None,
             config: None,
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -91,6 +93,8 @@ mod tests {
             royalty_info: None,
             config: Some(init_config),
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -188,6 +192,8 @@ mod tests {
             royalty_info: None,
             config: None,
             post_init_callback,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         let init_response = init(&mut deps, env, init_msg).unwrap();
@@ -246,6 +252,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT2".to_string()),
@@ -256,6 +263,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT3".to_string()),
@@ -266,6 +274,7 @@ mod tests {
                 transferable: None,
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: None,
@@ -276,6 +285,7 @@ mod tests {
                 transferable: None,
                 serial_number: None,
                 memo: Some("has id 3".to_string()),
+                hidden_attributes: None,
             },
         ];
 
@@ -6589,6 +6599,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT2".to_string()),
@@ -6599,6 +6610,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT3".to_string()),
@@ -6609,6 +6621,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT4".to_string()),
@@ -6619,6 +6632,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT5".to_string()),
@@ -6629,6 +6643,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT6".to_string()),
@@ -6639,6 +6654,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
             ],
             padding: None,
@@ -7190,6 +7206,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT2".to_string()),
@@ -7200,6 +7217,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT3".to_string()),
@@ -7210,6 +7228,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT4".to_string()),
@@ -7220,6 +7239,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT5".to_string()),
@@ -7230,6 +7250,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT6".to_string()),
@@ -7240,6 +7261,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
             ],
             padding: None,
@@ -8563,6 +8585,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT2".to_string()),
@@ -8573,6 +8596,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT3".to_string()),
@@ -8583,6 +8607,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT4".to_string()),
@@ -8593,6 +8618,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT5".to_string()),
@@ -8603,6 +8629,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
                 Mint {
                     token_id: Some("NFT6".to_string()),
@@ -8613,6 +8640,7 @@ mod tests {
                     serial_number: None,
                     transferable: None,
                     memo: None,
+                    hidden_attributes: None,
                 },
             ],
             padding: None,
@@ -9293,7 +9321,7 @@ mod tests {
         assert!(cur_minter.is_none());
     }
 
-    // test change admin
+    // test two step admin transfer
     #[test]
     fn test_change_admin() {
         let (init_result, mut deps) = init_helper_default();
@@ -9303,13 +9331,13 @@ mod tests {
             init_result.err().unwrap()
         );
 
-        // test changing admin when status prevents it
+        // test proposing admin when status prevents it
         let handle_msg = HandleMsg::SetContractStatus {
             level: ContractStatus::StopAll,
             padding: None,
         };
         let _handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
-        let handle_msg = HandleMsg::ChangeAdmin {
+        let handle_msg = HandleMsg::ProposeAdmin {
             address: HumanAddr("alice".to_string()),
             padding: None,
         };
@@ -9324,8 +9352,8 @@ mod tests {
         };
         let _handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
 
-        // test non admin trying to change admin
-        let handle_msg = HandleMsg::ChangeAdmin {
+        // test non admin trying to propose admin
+        let handle_msg = HandleMsg::ProposeAdmin {
             address: HumanAddr("alice".to_string()),
             padding: None,
         };
@@ -9344,18 +9372,51 @@ mod tests {
             .api
             .canonical_address(&HumanAddr("admin".to_string()))
             .unwrap();
-        // verify admin is the current admin
+        // propose alice, admin doesn't change until she accepts
+        let handle_msg = HandleMsg::ProposeAdmin {
+            address: HumanAddr("alice".to_string()),
+            padding: None,
+        };
+        let _handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
         let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
         assert_eq!(config.admin, admin_raw);
-        // change it to alice
-        let handle_msg = HandleMsg::ChangeAdmin {
+        let pending: Option<CanonicalAddr> = may_load(&deps.storage, PENDING_ADMIN_KEY).unwrap();
+        assert_eq!(pending, Some(alice_raw.clone()));
+
+        // test someone else trying to accept
+        let handle_msg = HandleMsg::AcceptAdmin { padding: None };
+        let handle_result = handle(&mut deps, mock_env("bob", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only the proposed admin can accept the admin authority"));
+
+        // test accepting a cancelled proposal
+        let handle_msg = HandleMsg::CancelAdminProposal { padding: None };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(
+            error.contains("This is an admin command and can only be run from the admin address")
+        );
+        let handle_msg = HandleMsg::CancelAdminProposal { padding: None };
+        let _handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let handle_msg = HandleMsg::AcceptAdmin { padding: None };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only the proposed admin can accept the admin authority"));
+
+        // propose alice again and let her accept
+        let handle_msg = HandleMsg::ProposeAdmin {
             address: HumanAddr("alice".to_string()),
             padding: None,
         };
         let _handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let handle_msg = HandleMsg::AcceptAdmin { padding: None };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        assert!(handle_result.is_ok());
         // verify admin was changed
         let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
         assert_eq!(config.admin, alice_raw);
+        let pending: Option<CanonicalAddr> = may_load(&deps.storage, PENDING_ADMIN_KEY).unwrap();
+        assert_eq!(pending, None);
     }
 
     // test set contract status
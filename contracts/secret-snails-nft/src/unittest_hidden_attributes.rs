@@ -0,0 +1,464 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{handle, init, query, query_batch_hidden_attributes};
+    use crate::msg::{
+        BatchHiddenAttributesElement, HandleMsg, InitMsg, Mint, QueryAnswer, QueryMsg,
+        QueryWithPermit, ViewerInfo,
+    };
+    use crate::token::HiddenAttribute;
+    use cosmwasm_std::testing::*;
+    use cosmwasm_std::{
+        from_binary, Api, Binary, Extern, HumanAddr, InitResponse, StdError, StdResult,
+    };
+    use secret_toolkit::permit::{Permission, Permit, PermitParams, PermitSignature, PubKey};
+    use std::any::Any;
+
+    // Helper functions
+
+    fn init_helper_hidden_viewers(
+        hidden_attribute_viewers: Option<Vec<HumanAddr>>,
+    ) -> (
+        StdResult<InitResponse>,
+        Extern<MockStorage, MockApi, MockQuerier>,
+    ) {
+        let mut deps = mock_dependencies(20, &[]);
+        let env = mock_env("instantiator", &[]);
+
+        let init_msg = InitMsg {
+            name: "sec721".to_string(),
+            symbol: "S721".to_string(),
+            admin: Some(HumanAddr("admin".to_string())),
+            entropy: "We're going to need a bigger boat".to_string(),
+            royalty_info: None,
+            config: None,
+            post_init_callback: None,
+            hidden_attribute_viewers,
+            minters: None,
+        };
+
+        (init(&mut deps, env, init_msg), deps)
+    }
+
+    fn extract_error_msg<T: Any>(error: StdResult<T>) -> String {
+        match error {
+            Ok(_response) => panic!("Expected error, but had Ok response"),
+            Err(err) => match err {
+                StdError::GenericErr { msg, .. } => msg,
+                _ => panic!("Unexpected error result {:?}", err),
+            },
+        }
+    }
+
+    fn speed(value: &str) -> Vec<HiddenAttribute> {
+        vec![HiddenAttribute {
+            name: "speed".to_string(),
+            value: value.to_string(),
+        }]
+    }
+
+    fn mint_snails(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
+        let alice = HumanAddr("alice".to_string());
+        let handle_msg = HandleMsg::BatchMintNft {
+            mints: vec![
+                Mint {
+                    token_id: Some("NFT1".to_string()),
+                    owner: Some(alice.clone()),
+                    public_metadata: None,
+                    private_metadata: None,
+                    royalty_info: None,
+                    serial_number: None,
+                    transferable: None,
+                    memo: None,
+                    hidden_attributes: Some(speed("42")),
+                },
+                Mint {
+                    token_id: Some("NFT2".to_string()),
+                    owner: Some(alice),
+                    public_metadata: None,
+                    private_metadata: None,
+                    royalty_info: None,
+                    serial_number: None,
+                    transferable: None,
+                    memo: None,
+                    hidden_attributes: None,
+                },
+            ],
+            padding: None,
+        };
+        handle(deps, mock_env("admin", &[]), handle_msg).unwrap();
+    }
+
+    fn set_key(deps: &mut Extern<MockStorage, MockApi, MockQuerier>, address: &str) {
+        let handle_msg = HandleMsg::SetViewingKey {
+            key: "key".to_string(),
+            padding: None,
+        };
+        handle(deps, mock_env(address, &[]), handle_msg).unwrap();
+    }
+
+    fn set_hidden_attributes_msg(token_id: &str, value: &str) -> HandleMsg {
+        HandleMsg::SetHiddenAttributes {
+            token_id: token_id.to_string(),
+            hidden_attributes: speed(value),
+            padding: None,
+        }
+    }
+
+    fn hidden_attributes_query(address: &str, token_id: &str) -> QueryMsg {
+        QueryMsg::HiddenAttributes {
+            token_id: token_id.to_string(),
+            viewer: ViewerInfo {
+                address: HumanAddr(address.to_string()),
+                viewing_key: "key".to_string(),
+            },
+        }
+    }
+
+    fn batch_hidden_attributes_query(address: &str, token_ids: &[&str]) -> QueryMsg {
+        QueryMsg::BatchHiddenAttributes {
+            token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+            viewer: ViewerInfo {
+                address: HumanAddr(address.to_string()),
+                viewing_key: "key".to_string(),
+            },
+        }
+    }
+
+    fn hidden_viewers(deps: &Extern<MockStorage, MockApi, MockQuerier>) -> Vec<HumanAddr> {
+        let query_result = query(deps, QueryMsg::HiddenAttributeViewers {});
+        let query_answer: QueryAnswer = from_binary(&query_result.unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::HiddenAttributeViewers { viewers } => viewers,
+            _ => panic!("unexpected"),
+        }
+    }
+
+    // Handle tests
+
+    #[test]
+    fn test_mint_hidden_attributes() {
+        let (init_result, mut deps) =
+            init_helper_hidden_viewers(Some(vec![HumanAddr("race".to_string())]));
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+        mint_snails(&mut deps);
+        set_key(&mut deps, "race");
+        set_key(&mut deps, "alice");
+        set_key(&mut deps, "admin");
+
+        // hidden attribute viewer can see them
+        let query_result = query(&deps, hidden_attributes_query("race", "NFT1"));
+        let query_answer: QueryAnswer = from_binary(&query_result.unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::HiddenAttributes { hidden_attributes } => {
+                assert_eq!(hidden_attributes, speed("42"));
+            }
+            _ => panic!("unexpected"),
+        }
+
+        // token minted without hidden attributes
+        let query_result = query(&deps, hidden_attributes_query("race", "NFT2"));
+        let query_answer: QueryAnswer = from_binary(&query_result.unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::HiddenAttributes { hidden_attributes } => {
+                assert!(hidden_attributes.is_empty());
+            }
+            _ => panic!("unexpected"),
+        }
+
+        // viewer is told when a token does not exist
+        let query_result = query(&deps, hidden_attributes_query("race", "NFT3"));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Token ID: NFT3 not found"));
+
+        // not even the owner or the admin can see them
+        let query_result = query(&deps, hidden_attributes_query("alice", "NFT1"));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+        let query_result = query(&deps, hidden_attributes_query("admin", "NFT1"));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+
+        // wrong viewing key
+        let query_msg = QueryMsg::HiddenAttributes {
+            token_id: "NFT1".to_string(),
+            viewer: ViewerInfo {
+                address: HumanAddr("race".to_string()),
+                viewing_key: "wrong".to_string(),
+            },
+        };
+        let query_result = query(&deps, query_msg);
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Wrong viewing key"));
+
+        // the owner's dossier does not leak them
+        let query_msg = QueryMsg::NftDossier {
+            token_id: "NFT1".to_string(),
+            viewer: Some(ViewerInfo {
+                address: HumanAddr("alice".to_string()),
+                viewing_key: "key".to_string(),
+            }),
+            include_expired: None,
+        };
+        let query_result = query(&deps, query_msg).unwrap();
+        let dossier = String::from_utf8(query_result.0).unwrap();
+        assert!(dossier.contains("\"nft_dossier\""));
+        assert!(!dossier.contains("speed"));
+    }
+
+    #[test]
+    fn test_no_hidden_attribute_viewers() {
+        let (init_result, mut deps) = init_helper_hidden_viewers(None);
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+        mint_snails(&mut deps);
+        set_key(&mut deps, "admin");
+
+        let query_result = query(&deps, hidden_attributes_query("admin", "NFT1"));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+    }
+
+    #[test]
+    fn test_set_hidden_attributes() {
+        let (init_result, mut deps) =
+            init_helper_hidden_viewers(Some(vec![HumanAddr("race".to_string())]));
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+        mint_snails(&mut deps);
+        set_key(&mut deps, "race");
+
+        // only minters can set them, not even the owner or a viewer
+        let handle_result = handle(
+            &mut deps,
+            mock_env("alice", &[]),
+            set_hidden_attributes_msg("NFT1", "100"),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Not authorized to update hidden attributes of token NFT1"));
+        let handle_result = handle(
+            &mut deps,
+            mock_env("race", &[]),
+            set_hidden_attributes_msg("NFT1", "100"),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Not authorized to update hidden attributes of token NFT1"));
+
+        let handle_result = handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            set_hidden_attributes_msg("NFT1", "100"),
+        );
+        assert!(handle_result.is_ok());
+        let query_result = query(&deps, hidden_attributes_query("race", "NFT1"));
+        let query_answer: QueryAnswer = from_binary(&query_result.unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::HiddenAttributes { hidden_attributes } => {
+                assert_eq!(hidden_attributes, speed("100"));
+            }
+            _ => panic!("unexpected"),
+        }
+
+        // token does not exist
+        let handle_result = handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            set_hidden_attributes_msg("NFT3", "100"),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Token ID: NFT3 not found"));
+    }
+
+    #[test]
+    fn test_hidden_attribute_viewers_handles() {
+        let (init_result, mut deps) = init_helper_hidden_viewers(None);
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+        mint_snails(&mut deps);
+        set_key(&mut deps, "race");
+        set_key(&mut deps, "race2");
+        let race = HumanAddr("race".to_string());
+        let race2 = HumanAddr("race2".to_string());
+        assert!(hidden_viewers(&deps).is_empty());
+
+        // only the admin can manage the viewers
+        let handle_msg = HandleMsg::AddHiddenAttributeViewers {
+            viewers: vec![race.clone()],
+            padding: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("This is an admin command"));
+        let handle_msg = HandleMsg::SetHiddenAttributeViewers {
+            viewers: vec![race.clone()],
+            padding: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("This is an admin command"));
+        let handle_msg = HandleMsg::RemoveHiddenAttributeViewers {
+            viewers: vec![race.clone()],
+            padding: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("This is an admin command"));
+
+        // adding twice does not duplicate
+        for _ in 0..2 {
+            let handle_msg = HandleMsg::AddHiddenAttributeViewers {
+                viewers: vec![race.clone(), race2.clone()],
+                padding: None,
+            };
+            let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+            assert!(handle_result.is_ok());
+        }
+        assert_eq!(hidden_viewers(&deps), vec![race.clone(), race2.clone()]);
+        let query_result = query(&deps, hidden_attributes_query("race2", "NFT1"));
+        assert!(query_result.is_ok());
+
+        let handle_msg = HandleMsg::RemoveHiddenAttributeViewers {
+            viewers: vec![race2.clone()],
+            padding: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        assert!(handle_result.is_ok());
+        assert_eq!(hidden_viewers(&deps), vec![race.clone()]);
+        let query_result = query(&deps, hidden_attributes_query("race2", "NFT1"));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+
+        let handle_msg = HandleMsg::SetHiddenAttributeViewers {
+            viewers: vec![race2.clone(), race2.clone()],
+            padding: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        assert!(handle_result.is_ok());
+        assert_eq!(hidden_viewers(&deps), vec![race2.clone()]);
+        let query_result = query(&deps, hidden_attributes_query("race", "NFT1"));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+
+        let handle_msg = HandleMsg::SetHiddenAttributeViewers {
+            viewers: vec![],
+            padding: None,
+        };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        assert!(handle_result.is_ok());
+        assert!(hidden_viewers(&deps).is_empty());
+    }
+
+    // Query tests
+
+    #[test]
+    fn test_batch_hidden_attributes() {
+        let (init_result, mut deps) =
+            init_helper_hidden_viewers(Some(vec![HumanAddr("race".to_string())]));
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+        mint_snails(&mut deps);
+        set_key(&mut deps, "race");
+        set_key(&mut deps, "alice");
+        let expected = vec![
+            BatchHiddenAttributesElement {
+                token_id: "NFT1".to_string(),
+                hidden_attributes: speed("42"),
+            },
+            BatchHiddenAttributesElement {
+                token_id: "NFT2".to_string(),
+                hidden_attributes: vec![],
+            },
+        ];
+
+        let query_result = query(
+            &deps,
+            batch_hidden_attributes_query("race", &["NFT1", "NFT2"]),
+        );
+        let query_answer: QueryAnswer = from_binary(&query_result.unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::BatchHiddenAttributes { hidden_attributes } => {
+                assert_eq!(hidden_attributes, expected);
+            }
+            _ => panic!("unexpected"),
+        }
+
+        // the owner is not a viewer
+        let query_result = query(
+            &deps,
+            batch_hidden_attributes_query("alice", &["NFT1", "NFT2"]),
+        );
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+
+        // one missing token fails the whole batch
+        let query_result = query(
+            &deps,
+            batch_hidden_attributes_query("race", &["NFT1", "NFT3"]),
+        );
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Token ID: NFT3 not found"));
+
+        // the address derived from a validated permit goes through the same check
+        let race_raw = deps
+            .api
+            .canonical_address(&HumanAddr("race".to_string()))
+            .unwrap();
+        let alice_raw = deps
+            .api
+            .canonical_address(&HumanAddr("alice".to_string()))
+            .unwrap();
+        let token_ids = vec!["NFT1".to_string(), "NFT2".to_string()];
+        let query_result =
+            query_batch_hidden_attributes(&deps, token_ids.clone(), None, Some(race_raw));
+        let query_answer: QueryAnswer = from_binary(&query_result.unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::BatchHiddenAttributes { hidden_attributes } => {
+                assert_eq!(hidden_attributes, expected);
+            }
+            _ => panic!("unexpected"),
+        }
+        let query_result = query_batch_hidden_attributes(&deps, token_ids, None, Some(alice_raw));
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Only hidden attribute viewers are allowed"));
+
+        // permits for other contracts are rejected before the query runs
+        let query_msg = QueryMsg::WithPermit {
+            permit: Permit {
+                params: PermitParams {
+                    allowed_tokens: vec![HumanAddr("othercontract".to_string())],
+                    permit_name: "race".to_string(),
+                    chain_id: "secret-4".to_string(),
+                    permissions: vec![Permission::Owner],
+                },
+                signature: PermitSignature {
+                    pub_key: PubKey {
+                        r#type: "tendermint/PubKeySecp256k1".to_string(),
+                        value: Binary::from(vec![2u8; 33]),
+                    },
+                    signature: Binary::from(vec![0u8; 64]),
+                },
+            },
+            query: QueryWithPermit::HiddenAttributes {
+                token_ids: vec!["NFT1".to_string()],
+            },
+        };
+        let query_result = query(&deps, query_msg);
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Permit doesn't apply to token"));
+    }
+}
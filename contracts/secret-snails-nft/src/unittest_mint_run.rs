@@ -24,6 +24,8 @@ mod tests {
             royalty_info: None,
             config: None,
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
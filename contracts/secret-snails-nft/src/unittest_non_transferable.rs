@@ -38,6 +38,8 @@ mod tests {
             royalty_info: None,
             config: None,
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -86,6 +88,8 @@ mod tests {
             royalty_info: None,
             config: Some(init_config),
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -135,6 +139,8 @@ mod tests {
             royalty_info,
             config: Some(init_config),
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -225,6 +231,8 @@ mod tests {
             royalty_info: None,
             config: None,
             post_init_callback,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         let init_response = init(&mut deps, env, init_msg).unwrap();
@@ -358,6 +366,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("TryDefaultRoys".to_string()),
@@ -368,6 +377,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
         ];
 
@@ -453,6 +463,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT2".to_string()),
@@ -463,6 +474,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
         ];
 
@@ -571,6 +583,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT2".to_string()),
@@ -581,6 +594,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT3".to_string()),
@@ -591,6 +605,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
         ];
 
@@ -814,6 +829,7 @@ mod tests {
                 transferable: Some(false),
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT2".to_string()),
@@ -824,6 +840,7 @@ mod tests {
                 transferable: None,
                 serial_number: None,
                 memo: None,
+                hidden_attributes: None,
             },
         ];
 
@@ -32,6 +32,8 @@ mod tests {
             royalty_info: None,
             config: None,
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -80,6 +82,8 @@ mod tests {
             royalty_info: None,
             config: Some(init_config),
             post_init_callback: None,
+            hidden_attribute_viewers: None,
+            minters: None,
         };
 
         (init(&mut deps, env, init_msg), deps)
@@ -148,6 +152,7 @@ mod tests {
                 minter_may_update_metadata,
                 owner_may_update_metadata,
                 burn_is_enabled,
+                pending_admin,
             } => {
                 assert_eq!(token_supply_is_public, false);
                 assert_eq!(owner_is_public, true);
@@ -156,6 +161,7 @@ mod tests {
                 assert_eq!(minter_may_update_metadata, true);
                 assert_eq!(owner_may_update_metadata, false);
                 assert_eq!(burn_is_enabled, true);
+                assert_eq!(pending_admin, None);
             }
             _ => panic!("unexpected"),
         }
@@ -3790,6 +3796,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT2".to_string()),
@@ -3800,6 +3807,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT3".to_string()),
@@ -3810,6 +3818,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
         ];
 
@@ -4348,6 +4357,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT2".to_string()),
@@ -4358,6 +4368,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
             Mint {
                 token_id: Some("NFT3".to_string()),
@@ -4368,6 +4379,7 @@ mod tests {
                 serial_number: None,
                 transferable: None,
                 memo: None,
+                hidden_attributes: None,
             },
         ];
 
@@ -0,0 +1,495 @@
+use cosmwasm_std::{
+    from_binary, log, to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HumanAddr,
+    InitResponse, Querier, StdError, StdResult, Storage,
+};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaChaRng;
+use secret_toolkit::crypto::{sha_256, Prng};
+use secret_toolkit::snip721::{
+    batch_transfer_nft_msg, register_receive_nft_msg, set_viewing_key_msg, Transfer,
+};
+use secret_toolkit::storage::{TypedStore, TypedStoreMut};
+use secret_toolkit::utils::{HandleCallback, Query};
+
+use crate::msg::{
+    HandleAnswer, HandleMsg, HandleReceiveMsg, InitMsg, MinterHandleMsg, NftsQueryAnswer,
+    NftsQueryMsg, QueryAnswer, QueryMsg, ResponseStatus, ViewerInfo,
+};
+use crate::state::{load, may_load, save, Config, Race, RaceEntry, BLOCK_SIZE};
+
+pub const CONFIG_KEY: &[u8] = b"config";
+pub const PRNG_SEED_KEY: &[u8] = b"prngseed";
+pub const VIEWING_KEY_KEY: &[u8] = b"viewingkey";
+pub const ENTRIES_KEY: &[u8] = b"entries";
+pub const RACES_COUNT_KEY: &[u8] = b"racescount";
+pub const PREFIX_RACES: &[u8] = b"racesprefix";
+pub const PENDING_ADMIN_KEY: &[u8] = b"pendingadmin";
+/// name of the hidden attribute the minter gives every snail
+pub const SPEED_ATTRIBUTE: &str = "speed";
+/// the luck rolled by each snail in a race goes from 1 to LUCK_MAX, the same range as the speed
+pub const LUCK_MAX: u32 = 100;
+
+pub fn init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: InitMsg,
+) -> StdResult<InitResponse> {
+    validate_race_size(msg.race_size)?;
+
+    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
+    let admin = msg.admin.unwrap_or(env.message.sender);
+
+    config_store.store(
+        CONFIG_KEY,
+        &Config {
+            admin,
+            nft_contract: msg.nft_contract.clone(),
+            minter_contract: msg.minter_contract,
+            race_size: msg.race_size,
+        },
+    )?;
+
+    let prng_seed: Vec<u8> = sha_256(base64::encode(msg.entropy).as_bytes()).to_vec();
+    // the key only has to be unknown to everybody else, the prng seed already is
+    let viewing_key = base64::encode(sha_256(&[prng_seed.as_slice(), b"viewing_key"].concat()));
+    save(&mut deps.storage, PRNG_SEED_KEY, &prng_seed)?;
+    save(&mut deps.storage, VIEWING_KEY_KEY, &viewing_key)?;
+    save(&mut deps.storage, ENTRIES_KEY, &Vec::<RaceEntry>::new())?;
+    save(&mut deps.storage, RACES_COUNT_KEY, &0u32)?;
+
+    Ok(InitResponse {
+        messages: vec![
+            register_receive_nft_msg(
+                env.contract_code_hash,
+                Some(true),
+                None,
+                BLOCK_SIZE,
+                msg.nft_contract.token_code_hash.clone(),
+                msg.nft_contract.contract_addr.clone(),
+            )?,
+            set_viewing_key_msg(
+                viewing_key,
+                None,
+                BLOCK_SIZE,
+                msg.nft_contract.token_code_hash,
+                msg.nft_contract.contract_addr,
+            )?,
+        ],
+        log: vec![],
+    })
+}
+
+pub fn handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> StdResult<HandleResponse> {
+    match msg {
+        HandleMsg::ReceiveNft {
+            sender,
+            token_id,
+            msg,
+        } => enter_race(deps, env, sender, vec![token_id], msg),
+        HandleMsg::BatchReceiveNft {
+            from,
+            token_ids,
+            msg,
+            ..
+        } => enter_race(deps, env, from, token_ids, msg),
+        HandleMsg::Withdraw { token_ids } => withdraw(deps, env, token_ids),
+        HandleMsg::UpdateRaceSize { race_size } => update_race_size(deps, env, race_size),
+        HandleMsg::ProposeAdmin { address } => propose_admin(deps, env, address),
+        HandleMsg::AcceptAdmin {} => accept_admin(deps, env),
+        HandleMsg::CancelAdminProposal {} => cancel_admin_proposal(deps, env),
+    }
+}
+
+/// Locks the received snails in the race that is filling up.  Every time the race fills up it is
+/// run and a new one starts with the remaining snails
+pub fn enter_race<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    owner: HumanAddr,
+    token_ids: Vec<String>,
+    msg: Option<Binary>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    if env.message.sender != config.nft_contract.contract_addr {
+        return Err(StdError::generic_err(
+            "Only snails from the nft contract can enter a race!",
+        ));
+    }
+
+    let msg = if let Some(msg) = msg {
+        msg
+    } else {
+        return Err(StdError::generic_err("Receive handler not found!"));
+    };
+    let HandleReceiveMsg::EnterRace {} = from_binary(&msg)?;
+
+    let speeds = query_speeds(deps, &env, &config, token_ids.clone())?;
+
+    let mut entries: Vec<RaceEntry> = load(&deps.storage, ENTRIES_KEY)?;
+    let mut messages = vec![];
+
+    for (token_id, speed) in token_ids.into_iter().zip(speeds) {
+        entries.push(RaceEntry {
+            token_id,
+            owner: owner.clone(),
+            speed,
+        });
+
+        if entries.len() >= config.race_size as usize {
+            messages.extend(run_race(deps, &env, &config, entries)?);
+            entries = vec![];
+        }
+    }
+
+    save(&mut deps.storage, ENTRIES_KEY, &entries)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::EnterRace {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Reads the hidden speed of each snail from the nft contract, in the same order as `token_ids`
+fn query_speeds<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    config: &Config,
+    token_ids: Vec<String>,
+) -> StdResult<Vec<u8>> {
+    let viewing_key: String = load(&deps.storage, VIEWING_KEY_KEY)?;
+
+    let NftsQueryAnswer::BatchHiddenAttributes { hidden_attributes } =
+        NftsQueryMsg::BatchHiddenAttributes {
+            token_ids,
+            viewer: ViewerInfo {
+                address: env.contract.address.clone(),
+                viewing_key,
+            },
+        }
+        .query(
+            &deps.querier,
+            config.nft_contract.token_code_hash.clone(),
+            config.nft_contract.contract_addr.clone(),
+        )?;
+
+    hidden_attributes
+        .iter()
+        .map(|element| {
+            element
+                .hidden_attributes
+                .iter()
+                .find(|attribute| attribute.name == SPEED_ATTRIBUTE)
+                .and_then(|attribute| attribute.value.parse().ok())
+                .ok_or_else(|| {
+                    StdError::generic_err(format!(
+                        "Snail {} has no speed and can't race",
+                        element.token_id
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// Runs a full race and returns the messages that give the snails back to their owners and
+/// record the results on the snails' Wins/Loses traits.  Each snail scores its speed plus a
+/// random luck roll, the highest score wins and ties go to the snail that entered first
+fn run_race<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    config: &Config,
+    entries: Vec<RaceEntry>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let race_id: u32 = load::<u32, _>(&deps.storage, RACES_COUNT_KEY)? + 1;
+    let prng_seed: Vec<u8> = load(&deps.storage, PRNG_SEED_KEY)?;
+
+    let mut scores: Vec<(usize, u32)> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let random_seed = new_entropy(
+                env,
+                prng_seed.as_ref(),
+                &race_id.to_le_bytes(),
+                &(index as u32).to_le_bytes(),
+            );
+            let mut rng = ChaChaRng::from_seed(random_seed);
+            (index, entry.speed as u32 + rng.gen_range(1, LUCK_MAX + 1))
+        })
+        .collect();
+    // stable sort, so ties keep the entry order
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+    let race = Race {
+        finishing_order: scores
+            .iter()
+            .map(|(index, _)| entries[*index].token_id.clone())
+            .collect(),
+        block_height: env.block.height,
+    };
+    let mut races_store = PrefixedStorage::new(PREFIX_RACES, &mut deps.storage);
+    save(&mut races_store, &race_id.to_le_bytes(), &race)?;
+    save(&mut deps.storage, RACES_COUNT_KEY, &race_id)?;
+
+    let mut messages = vec![return_snails_msg(config, &entries)?];
+
+    for (position, (index, _)) in scores.iter().enumerate() {
+        let winner = position == 0;
+        messages.push(
+            MinterHandleMsg::UpdateSnailStats {
+                token_id: entries[*index].token_id.clone(),
+                wins_delta: winner as u32,
+                loses_delta: !winner as u32,
+            }
+            .to_cosmos_msg(
+                config.minter_contract.token_code_hash.clone(),
+                config.minter_contract.contract_addr.clone(),
+                None,
+            )?,
+        );
+    }
+
+    Ok(messages)
+}
+
+/// Returns the message that transfers every snail in `entries` back to its owner
+fn return_snails_msg(config: &Config, entries: &[RaceEntry]) -> StdResult<CosmosMsg> {
+    let mut transfers: Vec<Transfer> = vec![];
+
+    for entry in entries {
+        if let Some(transfer) = transfers.iter_mut().find(|t| t.recipient == entry.owner) {
+            transfer.token_ids.push(entry.token_id.clone());
+        } else {
+            transfers.push(Transfer {
+                recipient: entry.owner.clone(),
+                token_ids: vec![entry.token_id.clone()],
+                memo: None,
+            });
+        }
+    }
+
+    batch_transfer_nft_msg(
+        transfers,
+        None,
+        BLOCK_SIZE,
+        config.nft_contract.token_code_hash.clone(),
+        config.nft_contract.contract_addr.clone(),
+    )
+}
+
+/// Lets owners take their snails back while the race is still filling up
+pub fn withdraw<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token_ids: Vec<String>,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let mut entries: Vec<RaceEntry> = load(&deps.storage, ENTRIES_KEY)?;
+    let mut withdrawn: Vec<RaceEntry> = vec![];
+
+    for token_id in token_ids {
+        let position = entries
+            .iter()
+            .position(|e| e.token_id == token_id && e.owner == env.message.sender)
+            .ok_or_else(|| {
+                StdError::generic_err(format!(
+                    "Snail {} is not waiting for a race or was not entered by you",
+                    token_id
+                ))
+            })?;
+        withdrawn.push(entries.remove(position));
+    }
+
+    save(&mut deps.storage, ENTRIES_KEY, &entries)?;
+
+    let messages = if withdrawn.is_empty() {
+        vec![]
+    } else {
+        vec![return_snails_msg(&config, &withdrawn)?]
+    };
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Withdraw {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn update_race_size<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    race_size: u8,
+) -> StdResult<HandleResponse> {
+    let mut config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    if env.message.sender != config.admin {
+        return Err(StdError::generic_err("Only admin can execute this action!"));
+    }
+
+    validate_race_size(race_size)?;
+
+    let entries: Vec<RaceEntry> = load(&deps.storage, ENTRIES_KEY)?;
+    if entries.len() >= race_size as usize {
+        return Err(StdError::generic_err(format!(
+            "Race size must be bigger than the {} snails already waiting",
+            entries.len()
+        )));
+    }
+
+    config.race_size = race_size;
+    TypedStoreMut::attach(&mut deps.storage).store(CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::UpdateRaceSize {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Proposes `address` as the new admin, the current admin keeps its rights until it accepts
+pub fn propose_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    address: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    if env.message.sender != config.admin {
+        return Err(StdError::generic_err("Only admin can execute this action!"));
+    }
+
+    save(&mut deps.storage, PENDING_ADMIN_KEY, &address)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("pending_admin", &address)],
+        data: Some(to_binary(&HandleAnswer::ProposeAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+/// Makes the proposed admin the admin, it has to send this itself so a mistyped address can't
+/// take over the contract
+pub fn accept_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let pending_admin: Option<HumanAddr> = may_load(&deps.storage, PENDING_ADMIN_KEY)?;
+    if pending_admin.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::generic_err(
+            "Only the proposed admin can accept the admin rights",
+        ));
+    }
+
+    let mut config_store = TypedStoreMut::attach(&mut deps.storage);
+    let mut config: Config = config_store.load(CONFIG_KEY)?;
+    config.admin = env.message.sender;
+    config_store.store(CONFIG_KEY, &config)?;
+    deps.storage.remove(PENDING_ADMIN_KEY);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AcceptAdmin {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+pub fn cancel_admin_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+
+    if env.message.sender != config.admin {
+        return Err(StdError::generic_err("Only admin can execute this action!"));
+    }
+
+    deps.storage.remove(PENDING_ADMIN_KEY);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::CancelAdminProposal {
+            status: ResponseStatus::Success,
+        })?),
+    })
+}
+
+fn validate_race_size(race_size: u8) -> StdResult<()> {
+    if race_size < 2 {
+        return Err(StdError::generic_err("A race needs at least 2 snails"));
+    }
+
+    Ok(())
+}
+
+pub fn new_entropy(env: &Env, seed: &[u8], entropy: &[u8], index: &[u8]) -> [u8; 32] {
+    // 16 here represents the lengths in bytes of the block height and time.
+    let entropy_len = 16 + env.message.sender.len() + entropy.len() + index.len();
+    let mut rng_entropy = Vec::with_capacity(entropy_len);
+    rng_entropy.extend_from_slice(&env.block.height.to_be_bytes());
+    rng_entropy.extend_from_slice(&env.block.time.to_be_bytes());
+    rng_entropy.extend_from_slice(env.message.sender.0.as_bytes());
+    rng_entropy.extend_from_slice(entropy);
+    rng_entropy.extend_from_slice(index);
+
+    let mut rng = Prng::new(seed, &rng_entropy);
+
+    rng.rand_bytes()
+}
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Info {} => query_info(deps),
+        QueryMsg::Race { race_id } => query_race(deps, race_id),
+    }
+}
+
+fn query_info<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<Binary> {
+    let config = TypedStore::<Config, S>::attach(&deps.storage).load(CONFIG_KEY)?;
+    let entries: Vec<RaceEntry> = load(&deps.storage, ENTRIES_KEY)?;
+
+    to_binary(&QueryAnswer::Info {
+        admin: config.admin,
+        pending_admin: may_load(&deps.storage, PENDING_ADMIN_KEY)?,
+        nft_contract: config.nft_contract,
+        minter_contract: config.minter_contract,
+        race_size: config.race_size,
+        current_entries: entries.into_iter().map(|e| e.token_id).collect(),
+        races_count: load(&deps.storage, RACES_COUNT_KEY)?,
+    })
+}
+
+fn query_race<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    race_id: u32,
+) -> StdResult<Binary> {
+    let races_store = ReadonlyPrefixedStorage::new(PREFIX_RACES, &deps.storage);
+    let race: Race = may_load(&races_store, &race_id.to_le_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Race {} not found", race_id)))?;
+
+    to_binary(&QueryAnswer::Race {
+        race_id,
+        finishing_order: race.finishing_order,
+        block_height: race.block_height,
+    })
+}
@@ -0,0 +1,7 @@
+pub mod contract;
+pub mod msg;
+pub mod state;
+mod unittest_handles;
+
+#[cfg(all(target_arch = "wasm32", not(feature = "library")))]
+cosmwasm_std::create_entry_points!(contract);
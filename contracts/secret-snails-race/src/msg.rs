@@ -0,0 +1,161 @@
+use cosmwasm_std::{Binary, HumanAddr};
+use schemars::JsonSchema;
+use secret_toolkit::utils::{HandleCallback, Query};
+use serde::{Deserialize, Serialize};
+
+use crate::state::{SecretContract, BLOCK_SIZE};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    pub admin: Option<HumanAddr>,
+    pub entropy: String,
+    pub nft_contract: SecretContract,
+    pub minter_contract: SecretContract,
+    /// number of snails that run in each race
+    pub race_size: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    /// Called by the nft contract when a snail is sent with SendNft
+    ReceiveNft {
+        sender: HumanAddr,
+        token_id: String,
+        msg: Option<Binary>,
+    },
+    /// Called by the nft contract when snails are sent with BatchSendNft
+    BatchReceiveNft {
+        sender: HumanAddr,
+        from: HumanAddr,
+        token_ids: Vec<String>,
+        msg: Option<Binary>,
+    },
+    /// Takes snails back out of the race that is still filling up
+    Withdraw {
+        token_ids: Vec<String>,
+    },
+    UpdateRaceSize {
+        race_size: u8,
+    },
+    /// Proposes a new admin, who becomes admin once it sends AcceptAdmin
+    ProposeAdmin {
+        address: HumanAddr,
+    },
+    /// Sent by the proposed admin to take over the admin rights
+    AcceptAdmin {},
+    /// Withdraws the pending admin proposal
+    CancelAdminProposal {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleReceiveMsg {
+    EnterRace {},
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleAnswer {
+    EnterRace { status: ResponseStatus },
+    Withdraw { status: ResponseStatus },
+    UpdateRaceSize { status: ResponseStatus },
+    ProposeAdmin { status: ResponseStatus },
+    AcceptAdmin { status: ResponseStatus },
+    CancelAdminProposal { status: ResponseStatus },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus {
+    Success,
+    Failure,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Info {},
+    Race { race_id: u32 },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryAnswer {
+    Info {
+        admin: HumanAddr,
+        /// address proposed as the new admin, waiting for it to accept
+        pending_admin: Option<HumanAddr>,
+        nft_contract: SecretContract,
+        minter_contract: SecretContract,
+        race_size: u8,
+        /// snails waiting in the race that is filling up
+        current_entries: Vec<String>,
+        races_count: u32,
+    },
+    Race {
+        race_id: u32,
+        /// token ids of the snails, the winner first
+        finishing_order: Vec<String>,
+        block_height: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MinterHandleMsg {
+    UpdateSnailStats {
+        token_id: String,
+        wins_delta: u32,
+        loses_delta: u32,
+    },
+}
+
+impl HandleCallback for MinterHandleMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftsQueryMsg {
+    /// displays the hidden attributes of multiple tokens.  Only the hidden attribute viewers
+    /// are permitted to view them
+    BatchHiddenAttributes {
+        token_ids: Vec<String>,
+        viewer: ViewerInfo,
+    },
+}
+
+impl Query for NftsQueryMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftsQueryAnswer {
+    BatchHiddenAttributes {
+        hidden_attributes: Vec<BatchHiddenAttributesElement>,
+    },
+}
+
+/// the address and viewing key making an authenticated query request
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ViewerInfo {
+    /// querying address
+    pub address: HumanAddr,
+    /// authentication key string
+    pub viewing_key: String,
+}
+
+/// the token id and hidden attributes of a single token response in a batch query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchHiddenAttributesElement {
+    pub token_id: String,
+    pub hidden_attributes: Vec<HiddenAttribute>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HiddenAttribute {
+    pub name: String,
+    pub value: String,
+}
@@ -0,0 +1,77 @@
+use cosmwasm_std::{HumanAddr, ReadonlyStorage, StdError, StdResult, Storage};
+use schemars::JsonSchema;
+use secret_toolkit::serialization::{Bincode2, Serde};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::any::type_name;
+
+pub const BLOCK_SIZE: usize = 256;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, JsonSchema)]
+pub struct SecretContract {
+    pub contract_addr: HumanAddr,
+    pub token_code_hash: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct Config {
+    pub admin: HumanAddr,
+    pub nft_contract: SecretContract,
+    pub minter_contract: SecretContract,
+    pub race_size: u8,
+}
+
+/// A snail locked in the contract waiting for its race to fill up
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RaceEntry {
+    pub token_id: String,
+    /// address the snail is returned to after the race
+    pub owner: HumanAddr,
+    /// hidden speed read when the snail entered, never exposed by queries
+    pub speed: u8,
+}
+
+/// A finished race
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Race {
+    /// token ids of the snails, the winner first
+    pub finishing_order: Vec<String>,
+    pub block_height: u64,
+}
+
+/// Returns StdResult<()> resulting from saving an item to storage
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the storage this item should go to
+/// * `key` - a byte slice representing the key to access the stored item
+/// * `value` - a reference to the item to store
+pub fn save<T: Serialize, S: Storage>(storage: &mut S, key: &[u8], value: &T) -> StdResult<()> {
+    storage.set(key, &Bincode2::serialize(value)?);
+    Ok(())
+}
+
+/// Returns StdResult<T> from retrieving the item with the specified key.  Returns a
+/// StdError::NotFound if there is no item with that key
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the storage this item is in
+/// * `key` - a byte slice representing the key that accesses the stored item
+pub fn load<T: DeserializeOwned, S: ReadonlyStorage>(storage: &S, key: &[u8]) -> StdResult<T> {
+    Bincode2::deserialize(
+        &storage
+            .get(key)
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))?,
+    )
+}
+
+pub fn may_load<T: DeserializeOwned, S: ReadonlyStorage>(
+    storage: &S,
+    key: &[u8],
+) -> StdResult<Option<T>> {
+    match storage.get(key) {
+        Some(value) => Bincode2::deserialize(&value).map(Some),
+        None => Ok(None),
+    }
+}
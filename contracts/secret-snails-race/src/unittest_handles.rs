@@ -0,0 +1,471 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{handle, init, query};
+    use crate::msg::{
+        BatchHiddenAttributesElement, HandleMsg, HandleReceiveMsg, HiddenAttribute, InitMsg,
+        MinterHandleMsg, NftsQueryAnswer, NftsQueryMsg, QueryAnswer, QueryMsg,
+    };
+    use crate::state::{SecretContract, BLOCK_SIZE};
+    use cosmwasm_std::testing::*;
+    use cosmwasm_std::{
+        from_binary, from_slice, to_binary, CosmosMsg, Empty, Extern, HandleResponse, HumanAddr,
+        InitResponse, Querier, QuerierResult, QueryRequest, StdError, StdResult, SystemError,
+        WasmMsg, WasmQuery,
+    };
+    use secret_toolkit::snip721::{batch_transfer_nft_msg, Transfer};
+    use std::any::Any;
+
+    // Helper functions
+
+    fn nft_contract() -> SecretContract {
+        SecretContract {
+            contract_addr: HumanAddr("snails".to_string()),
+            token_code_hash: "snails_hash".to_string(),
+        }
+    }
+
+    fn minter_contract() -> SecretContract {
+        SecretContract {
+            contract_addr: HumanAddr("minter".to_string()),
+            token_code_hash: "minter_hash".to_string(),
+        }
+    }
+
+    /// answers the nft contract's BatchHiddenAttributes query with the given speeds.  Snails
+    /// missing from the list have no hidden attributes
+    struct SpeedsQuerier {
+        speeds: Vec<(&'static str, u8)>,
+    }
+
+    impl Querier for SpeedsQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match request {
+                QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr, msg, ..
+                }) if contract_addr == nft_contract().contract_addr => {
+                    let NftsQueryMsg::BatchHiddenAttributes { token_ids, viewer } =
+                        from_binary(&msg).unwrap();
+                    assert_eq!(viewer.address, HumanAddr::from(MOCK_CONTRACT_ADDR));
+                    let hidden_attributes = token_ids
+                        .into_iter()
+                        .map(|token_id| BatchHiddenAttributesElement {
+                            hidden_attributes: self
+                                .speeds
+                                .iter()
+                                .filter(|(id, _)| *id == token_id)
+                                .map(|(_, speed)| HiddenAttribute {
+                                    name: "speed".to_string(),
+                                    value: speed.to_string(),
+                                })
+                                .collect(),
+                            token_id,
+                        })
+                        .collect();
+                    Ok(to_binary(&NftsQueryAnswer::BatchHiddenAttributes {
+                        hidden_attributes,
+                    }))
+                }
+                _ => Err(SystemError::NoSuchContract {
+                    addr: HumanAddr("unknown".to_string()),
+                }),
+            }
+        }
+    }
+
+    fn init_helper(
+        race_size: u8,
+    ) -> (
+        StdResult<InitResponse>,
+        Extern<MockStorage, MockApi, SpeedsQuerier>,
+    ) {
+        let mock_deps = mock_dependencies(20, &[]);
+        let mut deps = Extern {
+            storage: mock_deps.storage,
+            api: mock_deps.api,
+            querier: SpeedsQuerier {
+                speeds: vec![("1", 100), ("2", 50), ("3", 1), ("4", 70)],
+            },
+        };
+        let env = mock_env("instantiator", &[]);
+
+        let init_msg = InitMsg {
+            admin: Some(HumanAddr("admin".to_string())),
+            entropy: "We're going to need a bigger boat".to_string(),
+            nft_contract: nft_contract(),
+            minter_contract: minter_contract(),
+            race_size,
+        };
+
+        (init(&mut deps, env, init_msg), deps)
+    }
+
+    fn race_ready_helper(race_size: u8) -> Extern<MockStorage, MockApi, SpeedsQuerier> {
+        let (init_result, deps) = init_helper(race_size);
+        assert!(
+            init_result.is_ok(),
+            "Init failed: {}",
+            init_result.err().unwrap()
+        );
+        deps
+    }
+
+    fn enter_msg(owner: &str, token_ids: &[&str]) -> HandleMsg {
+        HandleMsg::BatchReceiveNft {
+            sender: HumanAddr(owner.to_string()),
+            from: HumanAddr(owner.to_string()),
+            token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+            msg: Some(to_binary(&HandleReceiveMsg::EnterRace {}).unwrap()),
+        }
+    }
+
+    fn enter(
+        deps: &mut Extern<MockStorage, MockApi, SpeedsQuerier>,
+        owner: &str,
+        token_ids: &[&str],
+    ) -> HandleResponse {
+        handle(
+            deps,
+            mock_env(nft_contract().contract_addr, &[]),
+            enter_msg(owner, token_ids),
+        )
+        .unwrap()
+    }
+
+    fn expected_return(transfers: Vec<(&str, Vec<&str>)>) -> CosmosMsg {
+        batch_transfer_nft_msg(
+            transfers
+                .into_iter()
+                .map(|(recipient, token_ids)| Transfer {
+                    recipient: HumanAddr(recipient.to_string()),
+                    token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+                    memo: None,
+                })
+                .collect(),
+            None,
+            BLOCK_SIZE,
+            nft_contract().token_code_hash,
+            nft_contract().contract_addr,
+        )
+        .unwrap()
+    }
+
+    /// returns (token_id, wins_delta, loses_delta) of an UpdateSnailStats message
+    fn extract_snail_stats(message: &CosmosMsg) -> (String, u32, u32) {
+        match message {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(*contract_addr, minter_contract().contract_addr);
+                let MinterHandleMsg::UpdateSnailStats {
+                    token_id,
+                    wins_delta,
+                    loses_delta,
+                } = from_binary(msg).unwrap();
+                (token_id, wins_delta, loses_delta)
+            }
+            _ => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    fn current_entries(deps: &Extern<MockStorage, MockApi, SpeedsQuerier>) -> Vec<String> {
+        let query_answer: QueryAnswer =
+            from_binary(&query(deps, QueryMsg::Info {}).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::Info {
+                current_entries, ..
+            } => current_entries,
+            _ => panic!("unexpected"),
+        }
+    }
+
+    fn extract_error_msg<T: Any>(error: StdResult<T>) -> String {
+        match error {
+            Ok(_response) => panic!("Expected error, but had Ok response"),
+            Err(err) => match err {
+                StdError::GenericErr { msg, .. } => msg,
+                _ => panic!("Unexpected error result {:?}", err),
+            },
+        }
+    }
+
+    // Init tests
+
+    #[test]
+    fn test_init() {
+        let (init_result, _deps) = init_helper(1);
+        let error = extract_error_msg(init_result);
+        assert!(error.contains("A race needs at least 2 snails"));
+
+        let (init_result, deps) = init_helper(3);
+        let init_response = init_result.unwrap();
+        // registers to receive snails and sets the key used to read their speed
+        assert_eq!(init_response.messages.len(), 2);
+        for message in init_response.messages.iter() {
+            match message {
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+                    assert_eq!(*contract_addr, nft_contract().contract_addr);
+                }
+                _ => panic!("unexpected message {:?}", message),
+            }
+        }
+        assert!(current_entries(&deps).is_empty());
+    }
+
+    // Handle tests
+
+    #[test]
+    fn test_enter_race_errors() {
+        let mut deps = race_ready_helper(3);
+
+        // snails can only come from the nft contract
+        let handle_result = handle(
+            &mut deps,
+            mock_env("alice", &[]),
+            enter_msg("alice", &["1"]),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only snails from the nft contract can enter a race!"));
+
+        let handle_msg = HandleMsg::ReceiveNft {
+            sender: HumanAddr("alice".to_string()),
+            token_id: "1".to_string(),
+            msg: None,
+        };
+        let handle_result = handle(
+            &mut deps,
+            mock_env(nft_contract().contract_addr, &[]),
+            handle_msg,
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Receive handler not found!"));
+
+        // a token without speed fails the whole entry
+        let handle_result = handle(
+            &mut deps,
+            mock_env(nft_contract().contract_addr, &[]),
+            enter_msg("alice", &["1", "5"]),
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Snail 5 has no speed and can't race"));
+        assert!(current_entries(&deps).is_empty());
+    }
+
+    #[test]
+    fn test_race() {
+        let mut deps = race_ready_helper(3);
+
+        let handle_response = enter(&mut deps, "alice", &["1", "2"]);
+        assert!(handle_response.messages.is_empty());
+        assert_eq!(current_entries(&deps), vec!["1", "2"]);
+
+        let handle_msg = HandleMsg::ReceiveNft {
+            sender: HumanAddr("bob".to_string()),
+            token_id: "3".to_string(),
+            msg: Some(to_binary(&HandleReceiveMsg::EnterRace {}).unwrap()),
+        };
+        let handle_response = handle(
+            &mut deps,
+            mock_env(nft_contract().contract_addr, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        assert!(current_entries(&deps).is_empty());
+
+        // snails go back to their owners and every one of them gets its result
+        let messages = handle_response.messages;
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[0],
+            expected_return(vec![("alice", vec!["1", "2"]), ("bob", vec!["3"])])
+        );
+        let stats: Vec<(String, u32, u32)> =
+            messages[1..].iter().map(extract_snail_stats).collect();
+
+        let query_answer: QueryAnswer =
+            from_binary(&query(&deps, QueryMsg::Race { race_id: 1 }).unwrap()).unwrap();
+        let finishing_order = match query_answer {
+            QueryAnswer::Race {
+                race_id,
+                finishing_order,
+                block_height,
+            } => {
+                assert_eq!(race_id, 1);
+                assert_eq!(block_height, mock_env("bob", &[]).block.height);
+                finishing_order
+            }
+            _ => panic!("unexpected"),
+        };
+        // the fastest snail can at worst tie with the slowest one, and ties go to the first entry
+        assert_eq!(finishing_order[0], "1");
+        assert_eq!(finishing_order.len(), 3);
+        assert_eq!(
+            stats,
+            vec![
+                (finishing_order[0].clone(), 1, 0),
+                (finishing_order[1].clone(), 0, 1),
+                (finishing_order[2].clone(), 0, 1),
+            ]
+        );
+
+        let query_result = query(&deps, QueryMsg::Race { race_id: 2 });
+        let error = extract_error_msg(query_result);
+        assert!(error.contains("Race 2 not found"));
+    }
+
+    #[test]
+    fn test_race_carries_over_extra_snails() {
+        let mut deps = race_ready_helper(2);
+
+        let handle_response = enter(&mut deps, "alice", &["1", "2", "3"]);
+        assert_eq!(handle_response.messages.len(), 3);
+        assert_eq!(
+            handle_response.messages[0],
+            expected_return(vec![("alice", vec!["1", "2"])])
+        );
+        assert_eq!(current_entries(&deps), vec!["3"]);
+
+        let handle_response = enter(&mut deps, "bob", &["4"]);
+        assert_eq!(handle_response.messages.len(), 3);
+        assert_eq!(
+            handle_response.messages[0],
+            expected_return(vec![("alice", vec!["3"]), ("bob", vec!["4"])])
+        );
+
+        let query_answer: QueryAnswer =
+            from_binary(&query(&deps, QueryMsg::Info {}).unwrap()).unwrap();
+        match query_answer {
+            QueryAnswer::Info {
+                races_count,
+                current_entries,
+                ..
+            } => {
+                assert_eq!(races_count, 2);
+                assert!(current_entries.is_empty());
+            }
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_withdraw() {
+        let mut deps = race_ready_helper(3);
+        enter(&mut deps, "alice", &["1", "2"]);
+
+        // only the address that entered the snail can take it back
+        let handle_msg = HandleMsg::Withdraw {
+            token_ids: vec!["1".to_string()],
+        };
+        let handle_result = handle(&mut deps, mock_env("bob", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Snail 1 is not waiting for a race or was not entered by you"));
+
+        let handle_msg = HandleMsg::Withdraw {
+            token_ids: vec!["3".to_string()],
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Snail 3 is not waiting for a race or was not entered by you"));
+
+        let handle_msg = HandleMsg::Withdraw {
+            token_ids: vec!["2".to_string()],
+        };
+        let handle_response = handle(&mut deps, mock_env("alice", &[]), handle_msg).unwrap();
+        assert_eq!(
+            handle_response.messages,
+            vec![expected_return(vec![("alice", vec!["2"])])]
+        );
+        assert_eq!(current_entries(&deps), vec!["1"]);
+
+        // the race still needs 2 more snails
+        let handle_response = enter(&mut deps, "bob", &["3"]);
+        assert!(handle_response.messages.is_empty());
+    }
+
+    #[test]
+    fn test_update_race_size() {
+        let mut deps = race_ready_helper(3);
+        enter(&mut deps, "alice", &["1", "2"]);
+
+        let handle_msg = HandleMsg::UpdateRaceSize { race_size: 4 };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+
+        let handle_msg = HandleMsg::UpdateRaceSize { race_size: 2 };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Race size must be bigger than the 2 snails already waiting"));
+
+        let handle_msg = HandleMsg::UpdateRaceSize { race_size: 4 };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg);
+        assert!(handle_result.is_ok());
+
+        let handle_response = enter(&mut deps, "bob", &["3"]);
+        assert!(handle_response.messages.is_empty());
+        let handle_response = enter(&mut deps, "bob", &["4"]);
+        assert_eq!(handle_response.messages.len(), 5);
+    }
+
+    fn admins<Q: Querier>(
+        deps: &Extern<MockStorage, MockApi, Q>,
+    ) -> (HumanAddr, Option<HumanAddr>) {
+        match from_binary(&query(deps, QueryMsg::Info {}).unwrap()).unwrap() {
+            QueryAnswer::Info {
+                admin,
+                pending_admin,
+                ..
+            } => (admin, pending_admin),
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let mut deps = race_ready_helper(3);
+        let admin = HumanAddr("admin".to_string());
+        let alice = HumanAddr("alice".to_string());
+
+        let propose_msg = HandleMsg::ProposeAdmin {
+            address: alice.clone(),
+        };
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), propose_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+
+        // nothing changes until the proposed admin accepts
+        handle(&mut deps, mock_env("admin", &[]), propose_msg.clone()).unwrap();
+        assert_eq!(admins(&deps), (admin.clone(), Some(alice.clone())));
+        let handle_result = handle(&mut deps, mock_env("bob", &[]), HandleMsg::AcceptAdmin {});
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only the proposed admin can accept the admin rights"));
+
+        // a cancelled proposal can't be accepted
+        let handle_result = handle(
+            &mut deps,
+            mock_env("alice", &[]),
+            HandleMsg::CancelAdminProposal {},
+        );
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+        handle(
+            &mut deps,
+            mock_env("admin", &[]),
+            HandleMsg::CancelAdminProposal {},
+        )
+        .unwrap();
+        assert_eq!(admins(&deps), (admin.clone(), None));
+        let handle_result = handle(&mut deps, mock_env("alice", &[]), HandleMsg::AcceptAdmin {});
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only the proposed admin can accept the admin rights"));
+
+        handle(&mut deps, mock_env("admin", &[]), propose_msg).unwrap();
+        handle(&mut deps, mock_env("alice", &[]), HandleMsg::AcceptAdmin {}).unwrap();
+        assert_eq!(admins(&deps), (alice, None));
+        let handle_msg = HandleMsg::UpdateRaceSize { race_size: 4 };
+        let handle_result = handle(&mut deps, mock_env("admin", &[]), handle_msg.clone());
+        let error = extract_error_msg(handle_result);
+        assert!(error.contains("Only admin can execute this action!"));
+        handle(&mut deps, mock_env("alice", &[]), handle_msg).unwrap();
+    }
+}